@@ -0,0 +1,65 @@
+//! Compares the public scan paths of the crate on the final 2023 canvas,
+//! run with `cargo bench --bench scan_image`.
+
+use std::time::{Duration, Instant};
+
+use rusty_rplace_tools::image_io::ImageIO;
+use rusty_rplace_tools::image_scanner::{Config, Coordinate, ImageScanner, Match, PatternLibrary};
+
+const ITERATIONS: u32 = 5;
+
+/// Fastest of `ITERATIONS` runs together with the result of the last one.
+fn fastest_run<T>(mut run: impl FnMut() -> T) -> (Duration, T) {
+    let mut fastest = Duration::MAX;
+    let mut result = None;
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        result = Some(run());
+        fastest = fastest.min(start.elapsed());
+    }
+
+    (fastest, result.unwrap())
+}
+
+fn sorted_coordinates(matches: &[Match]) -> Vec<Vec<Coordinate>> {
+    let mut coordinates: Vec<Vec<Coordinate>> = matches
+        .iter()
+        .map(|found_match| found_match.image_coordinates.clone())
+        .collect();
+    coordinates.sort();
+    coordinates
+}
+
+fn main() {
+    let scanner = ImageScanner::new(Config::new_default()).unwrap();
+    let scanned_image = ImageIO::load_image("assets/images/final_2023_place.png").unwrap();
+    let crewmate =
+        scanner.create_pattern(ImageIO::load_image("assets/images/crewmate.png").unwrap());
+
+    let mut library = PatternLibrary::new();
+    library
+        .add_pattern("crewmate", crewmate.clone(), 0)
+        .unwrap();
+
+    let (pattern_duration, pattern_matches) = fastest_run(|| {
+        scanner
+            .scan_image_for_patterns("crewmate", &crewmate, &scanned_image)
+            .unwrap()
+    });
+    let (library_duration, mut library_matches) =
+        fastest_run(|| scanner.scan_image_for_library(&library, &scanned_image));
+    let library_matches = library_matches.remove("crewmate").unwrap_or_default();
+
+    assert_eq!(
+        sorted_coordinates(&pattern_matches),
+        sorted_coordinates(&library_matches)
+    );
+
+    println!(
+        "pattern scan: {:?}, library scan: {:?}, {} matches",
+        pattern_duration,
+        library_duration,
+        pattern_matches.len()
+    );
+}
@@ -0,0 +1,243 @@
+use std::{collections::HashMap, error::Error};
+
+use chrono::NaiveDateTime;
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+use crate::{
+    canvas_replay::CanvasReplay,
+    feed_parser::CSVRecord,
+    image_scanner::{BoundingBox, ColorUtils, Coordinate},
+};
+
+use super::{ColorChange, DiffMask, DiffReport};
+
+const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+pub struct CanvasDiff {
+    mask: DiffMask,
+    tolerance: u8,
+    highlight_color: Rgba<u8>,
+}
+
+impl CanvasDiff {
+    pub fn new(mask: DiffMask, tolerance: u8, highlight_color: Rgba<u8>) -> Self {
+        CanvasDiff {
+            mask,
+            tolerance,
+            highlight_color,
+        }
+    }
+
+    pub fn new_default() -> Self {
+        CanvasDiff {
+            mask: DiffMask::new(),
+            tolerance: 0,
+            highlight_color: Rgba([255, 0, 255, 255]),
+        }
+    }
+
+    pub fn compare_snapshots(
+        &self,
+        replay: &CanvasReplay,
+        records: &[CSVRecord],
+        before: NaiveDateTime,
+        after: NaiveDateTime,
+    ) -> Result<DiffReport, Box<dyn Error>> {
+        self.compare(
+            &replay.snapshot(records, before),
+            &replay.snapshot(records, after),
+        )
+    }
+
+    pub fn compare(
+        &self,
+        before: &DynamicImage,
+        after: &DynamicImage,
+    ) -> Result<DiffReport, Box<dyn Error>> {
+        if before.dimensions() != after.dimensions() {
+            return Err(format!(
+                "Cannot compare images of different sizes: {:?} and {:?}",
+                before.dimensions(),
+                after.dimensions()
+            )
+            .into());
+        }
+
+        let (width, height) = after.dimensions();
+        let mut changed = vec![false; (width * height) as usize];
+        let mut changed_pixel_count = 0;
+        let mut color_changes: HashMap<Rgba<u8>, ColorChange> = HashMap::new();
+
+        let mut diff_image = RgbaImage::new(width, height);
+
+        for (x, y, after_color) in after.pixels() {
+            let before_color = before.get_pixel(x, y);
+
+            let is_changed = !self.mask.is_masked(x, y)
+                && !ColorUtils::equal_with_tolerance(before_color, after_color, self.tolerance);
+
+            if is_changed {
+                changed[(y * width + x) as usize] = true;
+                changed_pixel_count += 1;
+
+                color_changes.entry(before_color).or_default().removed += 1;
+                color_changes.entry(after_color).or_default().added += 1;
+
+                diff_image.put_pixel(x, y, self.highlight_color);
+            } else {
+                diff_image.put_pixel(x, y, fade(after_color));
+            }
+        }
+
+        Ok(DiffReport {
+            changed_pixel_count,
+            clusters: find_clusters(&changed, width, height),
+            color_changes,
+            diff_image: DynamicImage::ImageRgba8(diff_image),
+        })
+    }
+}
+
+fn fade(color: Rgba<u8>) -> Rgba<u8> {
+    let luma = (color.0[0] as u32 * 299 + color.0[1] as u32 * 587 + color.0[2] as u32 * 114) / 1000;
+    let faded = (luma / 4 + 191) as u8;
+
+    Rgba([faded, faded, faded, 255])
+}
+
+fn find_clusters(changed: &[bool], width: u32, height: u32) -> Vec<BoundingBox> {
+    let mut visited = vec![false; changed.len()];
+    let mut clusters = Vec::new();
+
+    for start_index in 0..changed.len() {
+        if !changed[start_index] || visited[start_index] {
+            continue;
+        }
+
+        let start = Coordinate::new(
+            (start_index as u32 % width) as i32,
+            (start_index as u32 / width) as i32,
+        );
+        let mut bounding_box = BoundingBox::from_coordinate(&start);
+        let mut stack = vec![start];
+        visited[start_index] = true;
+
+        while let Some(coordinate) = stack.pop() {
+            bounding_box.include(&coordinate);
+
+            for (offset_x, offset_y) in NEIGHBOR_OFFSETS {
+                let neighbor = Coordinate::new(coordinate.x + offset_x, coordinate.y + offset_y);
+
+                if neighbor.x < 0
+                    || neighbor.y < 0
+                    || neighbor.x >= width as i32
+                    || neighbor.y >= height as i32
+                {
+                    continue;
+                }
+
+                let neighbor_index = (neighbor.y as u32 * width + neighbor.x as u32) as usize;
+
+                if changed[neighbor_index] && !visited[neighbor_index] {
+                    visited[neighbor_index] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        clusters.push(bounding_box);
+    }
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CanvasDiff;
+    use crate::canvas_diff::{ColorChange, DiffMask};
+    use crate::image_io::ImageIO;
+    use crate::image_scanner::BoundingBox;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+    fn images() -> (DynamicImage, DynamicImage) {
+        let before = RgbaImage::from_pixel(10, 10, WHITE);
+        let mut after = before.clone();
+
+        after.put_pixel(1, 1, RED);
+        after.put_pixel(2, 2, RED);
+        after.put_pixel(7, 8, RED);
+
+        (
+            DynamicImage::ImageRgba8(before),
+            DynamicImage::ImageRgba8(after),
+        )
+    }
+
+    #[test]
+    fn test_compare_images() {
+        let (before, after) = images();
+
+        let report = CanvasDiff::new_default().compare(&before, &after).unwrap();
+
+        assert_eq!(report.changed_pixel_count, 3);
+        assert_eq!(
+            report.clusters,
+            vec![BoundingBox::new(1, 1, 2, 2), BoundingBox::new(7, 8, 7, 8)]
+        );
+        assert_eq!(
+            report.color_changes[&WHITE],
+            ColorChange {
+                removed: 3,
+                added: 0
+            }
+        );
+        assert_eq!(report.color_changes[&RED].added, 3);
+        assert_eq!(
+            report.diff_image.as_rgba8().unwrap().get_pixel(7, 8),
+            &Rgba([255, 0, 255, 255])
+        );
+    }
+
+    #[test]
+    fn test_compare_masked() {
+        let (before, after) = images();
+        let mask = DiffMask::new().exclude_region(BoundingBox::new(5, 5, 9, 9));
+
+        let report = CanvasDiff::new(mask, 0, RED)
+            .compare(&before, &after)
+            .unwrap();
+
+        assert_eq!(report.changed_pixel_count, 2);
+        assert_eq!(report.clusters, vec![BoundingBox::new(1, 1, 2, 2)]);
+    }
+
+    #[test]
+    fn test_compare_loaded_images() {
+        let before = ImageIO::load_image("assets/images/crewmate.png").unwrap();
+        let after = ImageIO::load_image("assets/images/reversed_crewmate.png").unwrap();
+
+        let report = CanvasDiff::new_default().compare(&before, &after).unwrap();
+
+        assert!(report.changed_pixel_count > 0);
+    }
+
+    #[test]
+    fn test_compare_different_sizes() {
+        let before = ImageIO::load_image("assets/images/crewmate.png").unwrap();
+        let after = ImageIO::load_image("assets/images/heart.png").unwrap();
+
+        assert!(CanvasDiff::new_default().compare(&before, &after).is_err());
+    }
+}
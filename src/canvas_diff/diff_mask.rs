@@ -0,0 +1,46 @@
+use image::{DynamicImage, GenericImageView};
+
+use crate::image_scanner::{BoundingBox, Coordinate};
+
+/// Regions left out of a diff.
+#[derive(Debug, Default)]
+pub struct DiffMask {
+    excluded_regions: Vec<BoundingBox>,
+    mask_image: Option<DynamicImage>,
+}
+
+impl DiffMask {
+    pub fn new() -> Self {
+        DiffMask::default()
+    }
+
+    pub fn exclude_region(mut self, region: BoundingBox) -> Self {
+        self.excluded_regions.push(region);
+        self
+    }
+
+    /// Every pixel of `mask_image` that is not fully transparent is excluded.
+    pub fn from_image(mask_image: DynamicImage) -> Self {
+        DiffMask {
+            excluded_regions: Vec::new(),
+            mask_image: Some(mask_image),
+        }
+    }
+
+    pub fn is_masked(&self, x: u32, y: u32) -> bool {
+        let coordinate = Coordinate::new(x as i32, y as i32);
+
+        if self
+            .excluded_regions
+            .iter()
+            .any(|region| region.contains(&coordinate))
+        {
+            return true;
+        }
+
+        match &self.mask_image {
+            Some(mask_image) if mask_image.in_bounds(x, y) => mask_image.get_pixel(x, y).0[3] != 0,
+            _ => false,
+        }
+    }
+}
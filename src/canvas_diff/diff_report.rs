@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+
+use image::{DynamicImage, Rgba};
+
+use crate::image_scanner::BoundingBox;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ColorChange {
+    /// Pixels that had this color before and something else after.
+    pub removed: u32,
+    /// Pixels that have this color after and something else before.
+    pub added: u32,
+}
+
+pub struct DiffReport {
+    pub changed_pixel_count: u32,
+    /// Bounding box of every 8-connected cluster of changed pixels.
+    pub clusters: Vec<BoundingBox>,
+    pub color_changes: HashMap<Rgba<u8>, ColorChange>,
+    /// Later image with unchanged pixels faded and changed pixels highlighted.
+    pub diff_image: DynamicImage,
+}
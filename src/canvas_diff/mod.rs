@@ -0,0 +1,7 @@
+pub use canvas_diff::CanvasDiff;
+pub use diff_mask::DiffMask;
+pub use diff_report::{ColorChange, DiffReport};
+
+mod canvas_diff;
+mod diff_mask;
+mod diff_report;
@@ -0,0 +1,204 @@
+use chrono::NaiveDateTime;
+use image::{DynamicImage, GrayImage, Luma, Rgba, RgbaImage};
+
+use crate::feed_parser::{CSVRecord, CanvasRegion, Coordinate, UserInterner};
+
+use super::reverse_replay::replay_reverse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementKind {
+    Pixel,
+    /// Moderator circle.
+    Circle,
+    /// Moderator rectangle.
+    Rectangle,
+}
+
+impl PlacementKind {
+    pub fn from_coordinate(coordinate: &Coordinate) -> Self {
+        match coordinate {
+            Coordinate::Point { .. } => PlacementKind::Pixel,
+            Coordinate::Circle { .. } => PlacementKind::Circle,
+            Coordinate::Rectangle { .. } => PlacementKind::Rectangle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlameEntry {
+    pub user_id: u32,
+    pub timestamp: NaiveDateTime,
+    pub kind: PlacementKind,
+    pub color: Rgba<u8>,
+}
+
+/// Per-pixel record of who produced the visible color of the canvas at a given time.
+pub struct BlameMap {
+    region: CanvasRegion,
+    timestamp: NaiveDateTime,
+    entries: Vec<Option<BlameEntry>>,
+    users: UserInterner,
+}
+
+impl BlameMap {
+    /// Builds the map from records ordered by timestamp, records placed after `timestamp` are ignored.
+    pub fn from_records(
+        records: &[CSVRecord],
+        region: &CanvasRegion,
+        timestamp: NaiveDateTime,
+    ) -> Self {
+        let records_until = records.partition_point(|record| record.get_timestamp() <= timestamp);
+        let reconstructed = replay_reverse(&records[..records_until], region);
+
+        let mut users = UserInterner::new();
+
+        let entries = reconstructed
+            .get_claims()
+            .iter()
+            .map(|claim| {
+                claim.map(|claim| {
+                    let record = &records[claim.record_index];
+
+                    BlameEntry {
+                        user_id: users.intern(record.get_user()),
+                        timestamp: record.get_timestamp(),
+                        kind: PlacementKind::from_coordinate(record.get_coordinate()),
+                        color: claim.color,
+                    }
+                })
+            })
+            .collect();
+
+        BlameMap {
+            region: *region,
+            timestamp,
+            entries,
+            users,
+        }
+    }
+
+    pub fn get_region(&self) -> &CanvasRegion {
+        &self.region
+    }
+
+    pub fn get_timestamp(&self) -> NaiveDateTime {
+        self.timestamp
+    }
+
+    pub fn get_entries(&self) -> &Vec<Option<BlameEntry>> {
+        &self.entries
+    }
+
+    pub fn get_users(&self) -> &UserInterner {
+        &self.users
+    }
+
+    /// Entry of the pixel at canvas coordinates, `None` if nothing was placed there yet.
+    pub fn blame(&self, x: i32, y: i32) -> Option<&BlameEntry> {
+        let (image_x, image_y) = self.region.to_image_position(x, y)?;
+
+        self.entries[(image_y * self.region.width + image_x) as usize].as_ref()
+    }
+
+    pub fn blame_user(&self, x: i32, y: i32) -> Option<&str> {
+        self.blame(x, y)
+            .and_then(|entry| self.users.get_user(entry.user_id))
+    }
+
+    /// Seconds since the last change of every pixel, row by row.
+    pub fn age_values(&self) -> Vec<Option<i64>> {
+        self.entries
+            .iter()
+            .map(|entry| entry.map(|entry| (self.timestamp - entry.timestamp).num_seconds()))
+            .collect()
+    }
+
+    /// Grayscale raster of pixel age, fresh pixels are white and pixels older than
+    /// `max_age_seconds` or never placed are black.
+    pub fn age_image(&self, max_age_seconds: i64) -> DynamicImage {
+        let ages = self.age_values();
+
+        let image = GrayImage::from_fn(self.region.width, self.region.height, |x, y| {
+            match ages[(y * self.region.width + x) as usize] {
+                Some(age) => {
+                    let freshness = 1.0 - (age as f64 / max_age_seconds.max(1) as f64).min(1.0);
+                    Luma([(freshness * 255.0).round() as u8])
+                }
+                None => Luma([0]),
+            }
+        });
+
+        DynamicImage::ImageLuma8(image)
+    }
+
+    /// Raster with a distinct color for every user, pixels never placed are transparent.
+    pub fn user_image(&self) -> DynamicImage {
+        let image = RgbaImage::from_fn(self.region.width, self.region.height, |x, y| {
+            match self.entries[(y * self.region.width + x) as usize] {
+                Some(entry) => user_color(entry.user_id),
+                None => Rgba([0, 0, 0, 0]),
+            }
+        });
+
+        DynamicImage::ImageRgba8(image)
+    }
+}
+
+fn user_color(user_id: u32) -> Rgba<u8> {
+    let hash = user_id.wrapping_add(1).wrapping_mul(0x9E37_79B9);
+
+    Rgba([
+        (hash >> 24) as u8,
+        (hash >> 16) as u8,
+        (hash >> 8) as u8,
+        255,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlameMap, PlacementKind};
+    use crate::feed_parser::CanvasRegion;
+    use crate::test_utils::parse_records;
+    use chrono::NaiveDateTime;
+    use image::{GenericImageView, Luma};
+
+    fn parse_timestamp(timestamp: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    const RECORDS: &str = "timestamp,user,coordinate,pixel_color
+2023-07-20 13:00:00.000 UTC,alice,\"0,0\",#000000
+2023-07-20 13:00:10.000 UTC,moderator,\"0,1,0,0\",#FFFFFF
+2023-07-20 13:00:20.000 UTC,bob,\"1,0\",#FF4500";
+
+    #[test]
+    fn test_blame_at_timestamp() {
+        let records = parse_records(RECORDS);
+        let region = CanvasRegion::new(0, 0, 2, 2);
+
+        let blame_map =
+            BlameMap::from_records(&records, &region, parse_timestamp("2023-07-20 13:00:15"));
+
+        let entry = blame_map.blame(1, 0).unwrap();
+
+        assert_eq!(entry.kind, PlacementKind::Rectangle);
+        assert_eq!(blame_map.blame_user(1, 0), Some("moderator"));
+        assert_eq!(blame_map.blame(0, 1), None);
+    }
+
+    #[test]
+    fn test_age_image() {
+        let records = parse_records(RECORDS);
+        let region = CanvasRegion::new(0, 0, 2, 2);
+
+        let blame_map =
+            BlameMap::from_records(&records, &region, parse_timestamp("2023-07-20 13:00:20"));
+        let age_image = blame_map.age_image(20);
+
+        assert_eq!(blame_map.age_values()[..2], [Some(10), Some(0)]);
+        assert_eq!(age_image.as_luma8().unwrap().get_pixel(0, 0), &Luma([128]));
+        assert_eq!(age_image.as_luma8().unwrap().get_pixel(1, 0), &Luma([255]));
+        assert_eq!(age_image.dimensions(), (2, 2));
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::NaiveDateTime;
+use image::DynamicImage;
+
+use crate::feed_parser::CSVRecord;
+
+use super::{
+    blame::BlameMap, pixel_metrics::PixelMetricsMap, reverse_replay::replay_reverse,
+    sequential_replay::replay_sequential, tiled_replay::replay_tiled, ReconstructedCanvas,
+    ReplayConfig, ReplayStrategy,
+};
+
+pub struct CanvasReplay {
+    config: ReplayConfig,
+}
+
+impl CanvasReplay {
+    pub fn new(config: ReplayConfig) -> Self {
+        CanvasReplay { config }
+    }
+
+    /// Replays all records, they are expected to be ordered by timestamp.
+    pub fn replay(&self, records: &[CSVRecord]) -> DynamicImage {
+        match self.config.strategy {
+            ReplayStrategy::Sequential => {
+                replay_sequential(records, &self.config.region, self.config.background_color)
+            }
+            ReplayStrategy::Tiled { tile_size } => replay_tiled(
+                records,
+                &self.config.region,
+                self.config.background_color,
+                tile_size,
+            ),
+            ReplayStrategy::Reverse => {
+                replay_reverse(records, &self.config.region).to_image(self.config.background_color)
+            }
+        }
+    }
+
+    /// State of the canvas after every record placed at or before `timestamp`.
+    pub fn snapshot(&self, records: &[CSVRecord], timestamp: NaiveDateTime) -> DynamicImage {
+        let records_until = records.partition_point(|record| record.get_timestamp() <= timestamp);
+
+        self.replay(&records[..records_until])
+    }
+
+    /// Like `snapshot`, but also keeps the record that won each pixel.
+    pub fn snapshot_with_records(
+        &self,
+        records: &[CSVRecord],
+        timestamp: NaiveDateTime,
+    ) -> ReconstructedCanvas {
+        let records_until = records.partition_point(|record| record.get_timestamp() <= timestamp);
+
+        replay_reverse(&records[..records_until], &self.config.region)
+    }
+
+    pub fn blame(&self, records: &[CSVRecord], timestamp: NaiveDateTime) -> BlameMap {
+        BlameMap::from_records(records, &self.config.region, timestamp)
+    }
+
+    pub fn pixel_metrics(
+        &self,
+        records: &[CSVRecord],
+        window_start: NaiveDateTime,
+        window_end: NaiveDateTime,
+    ) -> PixelMetricsMap {
+        PixelMetricsMap::from_records(records, &self.config.region, window_start, window_end)
+    }
+}
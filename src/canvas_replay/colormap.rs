@@ -0,0 +1,91 @@
+use image::Rgba;
+
+/// Piecewise linear gradient used to render scalar rasters.
+#[derive(Debug, Clone)]
+pub struct Colormap {
+    stops: Vec<Rgba<u8>>,
+    missing_color: Rgba<u8>,
+}
+
+impl Colormap {
+    pub fn new(stops: Vec<Rgba<u8>>, missing_color: Rgba<u8>) -> Self {
+        assert!(!stops.is_empty(), "colormap needs at least one stop");
+
+        Colormap {
+            stops,
+            missing_color,
+        }
+    }
+
+    pub fn grayscale() -> Self {
+        Colormap::new(
+            vec![Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 255])],
+            Rgba([0, 0, 0, 0]),
+        )
+    }
+
+    /// Approximation of matplotlib's viridis.
+    pub fn viridis() -> Self {
+        Colormap::new(
+            vec![
+                Rgba([68, 1, 84, 255]),
+                Rgba([59, 82, 139, 255]),
+                Rgba([33, 145, 140, 255]),
+                Rgba([94, 201, 98, 255]),
+                Rgba([253, 231, 37, 255]),
+            ],
+            Rgba([0, 0, 0, 0]),
+        )
+    }
+
+    pub fn heat() -> Self {
+        Colormap::new(
+            vec![
+                Rgba([0, 0, 0, 255]),
+                Rgba([190, 0, 57, 255]),
+                Rgba([255, 69, 0, 255]),
+                Rgba([255, 214, 53, 255]),
+                Rgba([255, 255, 255, 255]),
+            ],
+            Rgba([0, 0, 0, 0]),
+        )
+    }
+
+    /// Color of a value normalized to 0.0..=1.0, NaN maps to the missing color.
+    pub fn color_at(&self, value: f64) -> Rgba<u8> {
+        if value.is_nan() {
+            return self.missing_color;
+        }
+
+        let position = value.clamp(0.0, 1.0) * (self.stops.len() - 1) as f64;
+        let lower_index = position.floor() as usize;
+        let upper_index = (lower_index + 1).min(self.stops.len() - 1);
+        let fraction = position - lower_index as f64;
+
+        let lower = self.stops[lower_index];
+        let upper = self.stops[upper_index];
+
+        let mut color = [0; 4];
+        for (channel, (lower, upper)) in color.iter_mut().zip(lower.0.iter().zip(upper.0.iter())) {
+            *channel = (*lower as f64 + (*upper as f64 - *lower as f64) * fraction).round() as u8;
+        }
+
+        Rgba(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Colormap;
+    use image::Rgba;
+
+    #[test]
+    fn test_color_at_interpolates() {
+        let colormap = Colormap::grayscale();
+
+        assert_eq!(colormap.color_at(0.0), Rgba([0, 0, 0, 255]));
+        assert_eq!(colormap.color_at(0.5), Rgba([128, 128, 128, 255]));
+        assert_eq!(colormap.color_at(2.0), Rgba([255, 255, 255, 255]));
+        assert_eq!(colormap.color_at(f64::NAN), Rgba([0, 0, 0, 0]));
+    }
+}
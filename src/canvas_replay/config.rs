@@ -0,0 +1,38 @@
+use image::Rgba;
+
+use crate::feed_parser::CanvasRegion;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayStrategy {
+    /// Replays every event in order on a single image.
+    Sequential,
+    /// Buckets events into square tiles and replays each tile on its own rayon worker,
+    /// a `tile_size` of 0 is treated as 1.
+    Tiled { tile_size: u32 },
+    /// Walks events newest-first and stops once every pixel is claimed.
+    Reverse,
+}
+
+pub struct ReplayConfig {
+    pub region: CanvasRegion,
+    pub background_color: Rgba<u8>,
+    pub strategy: ReplayStrategy,
+}
+
+impl ReplayConfig {
+    pub fn new(region: CanvasRegion, background_color: Rgba<u8>, strategy: ReplayStrategy) -> Self {
+        ReplayConfig {
+            region,
+            background_color,
+            strategy,
+        }
+    }
+
+    pub fn new_default() -> Self {
+        ReplayConfig {
+            region: CanvasRegion::new_2023_canvas(),
+            background_color: Rgba([255, 255, 255, 255]),
+            strategy: ReplayStrategy::Tiled { tile_size: 250 },
+        }
+    }
+}
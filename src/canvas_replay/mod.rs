@@ -0,0 +1,15 @@
+pub use blame::{BlameEntry, BlameMap, PlacementKind};
+pub use canvas_replay::CanvasReplay;
+pub use colormap::Colormap;
+pub use config::{ReplayConfig, ReplayStrategy};
+pub use pixel_metrics::{PixelMetric, PixelMetricsMap};
+pub use reverse_replay::{PixelClaim, ReconstructedCanvas};
+
+mod blame;
+mod canvas_replay;
+mod colormap;
+mod config;
+mod pixel_metrics;
+mod reverse_replay;
+mod sequential_replay;
+mod tiled_replay;
@@ -0,0 +1,234 @@
+use std::{error::Error, fs::File, io::Write};
+
+use chrono::NaiveDateTime;
+use image::{DynamicImage, RgbaImage};
+
+use crate::feed_parser::{CSVRecord, CanvasRegion, UserInterner};
+
+use super::Colormap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelMetric {
+    /// Seconds between the last change of a pixel and the end of the window.
+    TimeSinceLastChange,
+    ChangeCount,
+    DistinctUsers,
+    DistinctColors,
+    /// Seconds between the start of the window and the first placement on a pixel.
+    FirstPlacementTime,
+}
+
+/// Per-pixel statistics of all records placed inside a time window.
+pub struct PixelMetricsMap {
+    region: CanvasRegion,
+    window_start: NaiveDateTime,
+    window_end: NaiveDateTime,
+    first_placements: Vec<Option<NaiveDateTime>>,
+    last_changes: Vec<Option<NaiveDateTime>>,
+    change_counts: Vec<u32>,
+    distinct_users: Vec<u32>,
+    distinct_colors: Vec<u32>,
+}
+
+impl PixelMetricsMap {
+    /// Collects metrics of records ordered by timestamp and placed within `window_start..=window_end`.
+    pub fn from_records(
+        records: &[CSVRecord],
+        region: &CanvasRegion,
+        window_start: NaiveDateTime,
+        window_end: NaiveDateTime,
+    ) -> Self {
+        let pixel_count = (region.width * region.height) as usize;
+
+        let mut first_placements = vec![None; pixel_count];
+        let mut last_changes = vec![None; pixel_count];
+        let mut change_counts = vec![0; pixel_count];
+        // Every placement adds a pair, duplicates are removed once after all records are read.
+        let mut user_pairs: Vec<(u32, u32)> = Vec::new();
+        let mut color_pairs: Vec<(u32, [u8; 4])> = Vec::new();
+
+        let mut users = UserInterner::new();
+
+        let window_start_index =
+            records.partition_point(|record| record.get_timestamp() < window_start);
+        let window_end_index =
+            records.partition_point(|record| record.get_timestamp() <= window_end);
+
+        for record in &records[window_start_index..window_end_index.max(window_start_index)] {
+            let timestamp = record.get_timestamp();
+            let user_id = users.intern(record.get_user());
+            let color = record.get_pixel_color().0;
+
+            record.get_coordinate().for_each_pixel(region, |x, y| {
+                let pixel_index = (y - region.y) as u32 * region.width + (x - region.x) as u32;
+                let index = pixel_index as usize;
+
+                first_placements[index].get_or_insert(timestamp);
+                last_changes[index] = Some(timestamp);
+                change_counts[index] += 1;
+
+                user_pairs.push((pixel_index, user_id));
+                color_pairs.push((pixel_index, color));
+            });
+        }
+
+        let distinct_users = count_distinct_per_pixel(user_pairs, pixel_count);
+        let distinct_colors = count_distinct_per_pixel(color_pairs, pixel_count);
+
+        PixelMetricsMap {
+            region: *region,
+            window_start,
+            window_end,
+            first_placements,
+            last_changes,
+            change_counts,
+            distinct_users,
+            distinct_colors,
+        }
+    }
+
+    pub fn get_region(&self) -> &CanvasRegion {
+        &self.region
+    }
+
+    /// Raw metric values row by row, pixels without any placement are NaN for time metrics.
+    pub fn values(&self, metric: PixelMetric) -> Vec<f64> {
+        match metric {
+            PixelMetric::TimeSinceLastChange => self
+                .last_changes
+                .iter()
+                .map(|timestamp| seconds_between(*timestamp, Some(self.window_end)))
+                .collect(),
+            PixelMetric::FirstPlacementTime => self
+                .first_placements
+                .iter()
+                .map(|timestamp| seconds_between(Some(self.window_start), *timestamp))
+                .collect(),
+            PixelMetric::ChangeCount => to_values(&self.change_counts),
+            PixelMetric::DistinctUsers => to_values(&self.distinct_users),
+            PixelMetric::DistinctColors => to_values(&self.distinct_colors),
+        }
+    }
+
+    /// Renders the metric normalized between zero and its highest value.
+    pub fn to_image(&self, metric: PixelMetric, colormap: &Colormap) -> DynamicImage {
+        let values = self.values(metric);
+        let max_value = values
+            .iter()
+            .copied()
+            .filter(|value| !value.is_nan())
+            .fold(0.0, f64::max)
+            .max(1.0);
+
+        let image = RgbaImage::from_fn(self.region.width, self.region.height, |x, y| {
+            colormap.color_at(values[(y * self.region.width + x) as usize] / max_value)
+        });
+
+        DynamicImage::ImageRgba8(image)
+    }
+
+    /// Writes the metric as little-endian `f32` values row by row.
+    pub fn save_raw(&self, metric: PixelMetric, path: &str) -> Result<(), Box<dyn Error>> {
+        let bytes: Vec<u8> = self
+            .values(metric)
+            .into_iter()
+            .flat_map(|value| (value as f32).to_le_bytes())
+            .collect();
+
+        File::create(path)?.write_all(&bytes)?;
+
+        Ok(())
+    }
+}
+
+/// Number of different values paired with every pixel index.
+fn count_distinct_per_pixel<T: Ord>(mut pairs: Vec<(u32, T)>, pixel_count: usize) -> Vec<u32> {
+    pairs.sort_unstable();
+    pairs.dedup();
+
+    let mut counts = vec![0; pixel_count];
+    for (pixel_index, _) in pairs {
+        counts[pixel_index as usize] += 1;
+    }
+
+    counts
+}
+
+fn seconds_between(from: Option<NaiveDateTime>, to: Option<NaiveDateTime>) -> f64 {
+    match (from, to) {
+        (Some(from), Some(to)) => (to - from).num_milliseconds() as f64 / 1000.0,
+        _ => f64::NAN,
+    }
+}
+
+fn to_values(counts: &[u32]) -> Vec<f64> {
+    counts.iter().map(|count| *count as f64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PixelMetric, PixelMetricsMap};
+    use crate::canvas_replay::Colormap;
+    use crate::feed_parser::CanvasRegion;
+    use crate::test_utils::parse_records;
+    use chrono::NaiveDateTime;
+    use image::Rgba;
+
+    fn parse_timestamp(timestamp: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn metrics_map() -> PixelMetricsMap {
+        let records = parse_records(
+            "timestamp,user,coordinate,pixel_color
+2023-07-20 12:59:00.000 UTC,alice,\"1,0\",#000000
+2023-07-20 13:00:00.000 UTC,alice,\"0,0\",#000000
+2023-07-20 13:00:10.000 UTC,bob,\"0,0\",#FF4500
+2023-07-20 13:00:20.000 UTC,alice,\"0,0\",#000000
+2023-07-20 13:00:30.000 UTC,bob,\"0,1,0,0\",#000000
+2023-07-20 13:01:00.000 UTC,carol,\"0,0\",#FFFFFF",
+        );
+
+        PixelMetricsMap::from_records(
+            &records,
+            &CanvasRegion::new(0, 0, 2, 1),
+            parse_timestamp("2023-07-20 13:00:00"),
+            parse_timestamp("2023-07-20 13:00:40"),
+        )
+    }
+
+    #[test]
+    fn test_count_metrics() {
+        let metrics = metrics_map();
+
+        assert_eq!(metrics.values(PixelMetric::ChangeCount), vec![4.0, 1.0]);
+        assert_eq!(metrics.values(PixelMetric::DistinctUsers), vec![2.0, 1.0]);
+        assert_eq!(metrics.values(PixelMetric::DistinctColors), vec![2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_time_metrics() {
+        let metrics = metrics_map();
+
+        assert_eq!(
+            metrics.values(PixelMetric::TimeSinceLastChange),
+            vec![10.0, 10.0]
+        );
+        assert_eq!(
+            metrics.values(PixelMetric::FirstPlacementTime),
+            vec![0.0, 30.0]
+        );
+    }
+
+    #[test]
+    fn test_to_image() {
+        let metrics = metrics_map();
+
+        let image = metrics
+            .to_image(PixelMetric::ChangeCount, &Colormap::grayscale())
+            .into_rgba8();
+
+        assert_eq!(image.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(image.get_pixel(1, 0), &Rgba([64, 64, 64, 255]));
+    }
+}
@@ -0,0 +1,124 @@
+use image::{DynamicImage, Rgba, RgbaImage};
+
+use crate::feed_parser::{CSVRecord, CanvasRegion};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelClaim {
+    /// Index of the winning record in the replayed slice.
+    pub record_index: usize,
+    pub color: Rgba<u8>,
+}
+
+pub struct ReconstructedCanvas {
+    region: CanvasRegion,
+    claims: Vec<Option<PixelClaim>>,
+}
+
+impl ReconstructedCanvas {
+    pub fn get_region(&self) -> &CanvasRegion {
+        &self.region
+    }
+
+    pub fn get_claims(&self) -> &Vec<Option<PixelClaim>> {
+        &self.claims
+    }
+
+    /// Claim of the pixel at canvas coordinates, `None` if nothing was ever placed there.
+    pub fn get_claim(&self, x: i32, y: i32) -> Option<&PixelClaim> {
+        let (image_x, image_y) = self.region.to_image_position(x, y)?;
+
+        self.claims[(image_y * self.region.width + image_x) as usize].as_ref()
+    }
+
+    pub fn to_image(&self, background_color: Rgba<u8>) -> DynamicImage {
+        let image = RgbaImage::from_fn(self.region.width, self.region.height, |x, y| {
+            match self.claims[(y * self.region.width + x) as usize] {
+                Some(claim) => claim.color,
+                None => background_color,
+            }
+        });
+
+        DynamicImage::ImageRgba8(image)
+    }
+}
+
+/// Walks records newest-first and lets the first record covering a pixel claim it,
+/// stops as soon as every pixel of the region is claimed.
+pub fn replay_reverse(records: &[CSVRecord], region: &CanvasRegion) -> ReconstructedCanvas {
+    let mut claims = vec![None; (region.width * region.height) as usize];
+    let mut unclaimed_pixels = claims.len();
+
+    for (record_index, record) in records.iter().enumerate().rev() {
+        if unclaimed_pixels == 0 {
+            break;
+        }
+
+        record.get_coordinate().for_each_pixel(region, |x, y| {
+            let claim = &mut claims
+                [((y - region.y) as u32 * region.width + (x - region.x) as u32) as usize];
+
+            if claim.is_none() {
+                *claim = Some(PixelClaim {
+                    record_index,
+                    color: record.get_pixel_color(),
+                });
+                unclaimed_pixels -= 1;
+            }
+        });
+    }
+
+    ReconstructedCanvas {
+        region: *region,
+        claims,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{replay_reverse, PixelClaim};
+    use crate::canvas_replay::sequential_replay::replay_sequential;
+    use crate::feed_parser::{CanvasRegion, FeedParser};
+    use crate::test_utils::parse_records;
+    use image::Rgba;
+
+    const BACKGROUND_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    #[test]
+    fn test_reverse_replay_matches_sequential_replay() {
+        let records = FeedParser::parse_files(vec![
+            "assets/rplaceSample/2023_place_canvas_history-000000000000.csv",
+            "assets/rplaceSample/2023_place_canvas_history-000000000001.csv",
+        ])
+        .unwrap();
+        let region = CanvasRegion::new(-500, -500, 1000, 1000);
+
+        let sequential = replay_sequential(&records, &region, BACKGROUND_COLOR);
+        let reverse = replay_reverse(&records, &region).to_image(BACKGROUND_COLOR);
+
+        assert_eq!(sequential, reverse);
+    }
+
+    #[test]
+    fn test_reverse_replay_winning_records() {
+        let records = parse_records(
+            "timestamp,user,coordinate,pixel_color
+2023-07-20 13:00:00.000 UTC,user,\"0,0\",#000000
+2023-07-20 13:00:01.000 UTC,user,\"-1,1,-1,0\",#FF4500
+2023-07-20 13:00:02.000 UTC,user,\"0,-1\",#2450A4",
+        );
+        let region = CanvasRegion::new(-1, -1, 3, 3);
+
+        let reconstructed = replay_reverse(&records, &region);
+
+        assert_eq!(
+            reconstructed.get_claim(0, -1),
+            Some(&PixelClaim {
+                record_index: 2,
+                color: Rgba([36, 80, 164, 255])
+            })
+        );
+        assert_eq!(reconstructed.get_claim(0, 0).unwrap().record_index, 1);
+        assert_eq!(reconstructed.get_claim(1, 1), None);
+        assert_eq!(reconstructed.get_claim(5, 5), None);
+    }
+}
@@ -0,0 +1,17 @@
+use image::{DynamicImage, Rgba, RgbaImage};
+
+use crate::feed_parser::{CSVRecord, CanvasRegion};
+
+pub fn replay_sequential(
+    records: &[CSVRecord],
+    region: &CanvasRegion,
+    background_color: Rgba<u8>,
+) -> DynamicImage {
+    let mut image = RgbaImage::from_pixel(region.width, region.height, background_color);
+
+    for record in records {
+        record.draw_on_image(&mut image, region);
+    }
+
+    DynamicImage::ImageRgba8(image)
+}
@@ -0,0 +1,160 @@
+use image::{DynamicImage, GenericImage, Rgba, RgbaImage};
+use rayon::prelude::*;
+
+use crate::feed_parser::{CSVRecord, CanvasRegion};
+
+/// A `tile_size` of 0 is treated as 1.
+pub fn replay_tiled(
+    records: &[CSVRecord],
+    region: &CanvasRegion,
+    background_color: Rgba<u8>,
+    tile_size: u32,
+) -> DynamicImage {
+    let tile_size = tile_size.max(1);
+    let tiles_x = region.width.div_ceil(tile_size);
+    let tiles_y = region.height.div_ceil(tile_size);
+
+    let buckets = bucket_records_into_tiles(records, region, tile_size, tiles_x, tiles_y);
+
+    let tiles: Vec<(CanvasRegion, RgbaImage)> = buckets
+        .into_par_iter()
+        .enumerate()
+        .map(|(tile_index, bucket)| {
+            let tile_region = tile_region(
+                region,
+                tile_size,
+                tile_index as u32 % tiles_x,
+                tile_index as u32 / tiles_x,
+            );
+            let mut tile =
+                RgbaImage::from_pixel(tile_region.width, tile_region.height, background_color);
+
+            for record_index in bucket {
+                records[record_index].draw_on_image(&mut tile, &tile_region);
+            }
+
+            (tile_region, tile)
+        })
+        .collect();
+
+    let mut image = RgbaImage::new(region.width, region.height);
+
+    for (tile_region, tile) in tiles {
+        image
+            .copy_from(
+                &tile,
+                (tile_region.x - region.x) as u32,
+                (tile_region.y - region.y) as u32,
+            )
+            .expect("tile lies inside of the replayed region");
+    }
+
+    DynamicImage::ImageRgba8(image)
+}
+
+/// Indices of records touching each tile, shapes spanning several tiles land in every one of them.
+fn bucket_records_into_tiles(
+    records: &[CSVRecord],
+    region: &CanvasRegion,
+    tile_size: u32,
+    tiles_x: u32,
+    tiles_y: u32,
+) -> Vec<Vec<usize>> {
+    let mut buckets = vec![Vec::new(); (tiles_x * tiles_y) as usize];
+
+    for (record_index, record) in records.iter().enumerate() {
+        let record_region = match record
+            .get_coordinate()
+            .bounding_region()
+            .intersection(region)
+        {
+            Some(record_region) => record_region,
+            None => continue,
+        };
+
+        let first_tile_x = (record_region.x - region.x) as u32 / tile_size;
+        let first_tile_y = (record_region.y - region.y) as u32 / tile_size;
+        let last_tile_x = (record_region.max_x() - region.x) as u32 / tile_size;
+        let last_tile_y = (record_region.max_y() - region.y) as u32 / tile_size;
+
+        for tile_y in first_tile_y..=last_tile_y {
+            for tile_x in first_tile_x..=last_tile_x {
+                buckets[(tile_y * tiles_x + tile_x) as usize].push(record_index);
+            }
+        }
+    }
+
+    buckets
+}
+
+fn tile_region(region: &CanvasRegion, tile_size: u32, tile_x: u32, tile_y: u32) -> CanvasRegion {
+    let offset_x = tile_x * tile_size;
+    let offset_y = tile_y * tile_size;
+
+    CanvasRegion::new(
+        region.x + offset_x as i32,
+        region.y + offset_y as i32,
+        tile_size.min(region.width - offset_x),
+        tile_size.min(region.height - offset_y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::replay_tiled;
+    use crate::canvas_replay::sequential_replay::replay_sequential;
+    use crate::feed_parser::{CanvasRegion, FeedParser};
+    use crate::test_utils::parse_records;
+    use image::Rgba;
+
+    const BACKGROUND_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    #[test]
+    fn test_tiled_replay_matches_sequential_replay() {
+        let records = FeedParser::parse_files(vec![
+            "assets/rplaceSample/2023_place_canvas_history-000000000000.csv",
+            "assets/rplaceSample/2023_place_canvas_history-000000000001.csv",
+        ])
+        .unwrap();
+        let region = CanvasRegion::new(-500, -500, 1000, 1000);
+
+        let sequential = replay_sequential(&records, &region, BACKGROUND_COLOR);
+        let tiled = replay_tiled(&records, &region, BACKGROUND_COLOR, 64);
+
+        assert_eq!(sequential, tiled);
+    }
+
+    #[test]
+    fn test_tiled_replay_splits_shapes_across_tiles() {
+        let records = parse_records(
+            "timestamp,user,coordinate,pixel_color
+2023-07-20 13:00:00.000 UTC,user,\"-3,3,-3,3\",#FF4500
+2023-07-20 13:00:01.000 UTC,user,\"1,-1,4\",#2450A4
+2023-07-20 13:00:02.000 UTC,user,\"0,0\",#000000",
+        );
+        let region = CanvasRegion::new(-8, -8, 15, 15);
+
+        let sequential = replay_sequential(&records, &region, BACKGROUND_COLOR);
+        let tiled = replay_tiled(&records, &region, BACKGROUND_COLOR, 4);
+
+        assert_eq!(sequential, tiled);
+        assert_eq!(
+            tiled.as_rgba8().unwrap().get_pixel(8, 8),
+            &Rgba([0, 0, 0, 255])
+        );
+    }
+
+    #[test]
+    fn test_tiled_replay_with_zero_tile_size() {
+        let records = parse_records(
+            "timestamp,user,coordinate,pixel_color
+2023-07-20 13:00:00.000 UTC,user,\"1,2\",#FF4500",
+        );
+        let region = CanvasRegion::new(0, 0, 4, 4);
+
+        assert_eq!(
+            replay_tiled(&records, &region, BACKGROUND_COLOR, 0),
+            replay_sequential(&records, &region, BACKGROUND_COLOR)
+        );
+    }
+}
@@ -0,0 +1,99 @@
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct CanvasRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CanvasRegion {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        CanvasRegion {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Canvas of the 2023 event in its final size, coordinates range from (-1500, -1000) to (1499, 999).
+    pub fn new_2023_canvas() -> Self {
+        CanvasRegion::new(-1500, -1000, 3000, 2000)
+    }
+
+    pub fn from_corners(min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Self {
+        CanvasRegion {
+            x: min_x,
+            y: min_y,
+            width: (max_x - min_x + 1) as u32,
+            height: (max_y - min_y + 1) as u32,
+        }
+    }
+
+    pub fn max_x(&self) -> i32 {
+        self.x + self.width as i32 - 1
+    }
+
+    pub fn max_y(&self) -> i32 {
+        self.y + self.height as i32 - 1
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x <= self.max_x() && y <= self.max_y()
+    }
+
+    pub fn intersection(&self, other: &CanvasRegion) -> Option<CanvasRegion> {
+        let min_x = self.x.max(other.x);
+        let min_y = self.y.max(other.y);
+        let max_x = self.max_x().min(other.max_x());
+        let max_y = self.max_y().min(other.max_y());
+
+        if min_x > max_x || min_y > max_y {
+            return None;
+        }
+
+        Some(CanvasRegion::from_corners(min_x, min_y, max_x, max_y))
+    }
+
+    /// Position of a canvas coordinate inside an image covering this region.
+    pub fn to_image_position(&self, x: i32, y: i32) -> Option<(u32, u32)> {
+        if self.contains(x, y) {
+            Some(((x - self.x) as u32, (y - self.y) as u32))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CanvasRegion;
+
+    #[test]
+    fn test_intersection_overlapping() {
+        let region = CanvasRegion::new(0, 0, 10, 10);
+        let other = CanvasRegion::new(5, -5, 10, 10);
+
+        assert_eq!(
+            region.intersection(&other),
+            Some(CanvasRegion::new(5, 0, 5, 5))
+        );
+    }
+
+    #[test]
+    fn test_intersection_disjoint() {
+        let region = CanvasRegion::new(0, 0, 10, 10);
+        let other = CanvasRegion::new(10, 0, 10, 10);
+
+        assert_eq!(region.intersection(&other), None);
+    }
+
+    #[test]
+    fn test_to_image_position() {
+        let region = CanvasRegion::new_2023_canvas();
+
+        assert_eq!(region.to_image_position(-1500, -1000), Some((0, 0)));
+        assert_eq!(region.to_image_position(1499, 999), Some((2999, 1999)));
+        assert_eq!(region.to_image_position(1500, 0), None);
+    }
+}
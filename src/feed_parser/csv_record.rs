@@ -1,7 +1,8 @@
 use chrono::NaiveDateTime;
-use image::{DynamicImage, GenericImage, Rgba};
+use image::{Rgba, RgbaImage};
 use serde::de;
-use serde_derive;
+
+use super::CanvasRegion;
 
 #[derive(Debug, serde_derive::Deserialize)]
 pub struct CSVRecord {
@@ -19,6 +20,7 @@ where
     D: serde::Deserializer<'de>,
 {
     let timestamp_str: &str = serde::Deserialize::deserialize(deserializer)?;
+    let timestamp_str = timestamp_str.trim_end_matches(" UTC");
 
     NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%.f").map_err(de::Error::custom)
 }
@@ -86,29 +88,90 @@ fn parse_color_from_hex(color_str: &str) -> Result<Rgba<u8>, &'static str> {
 }
 
 impl CSVRecord {
-    fn draw_on_image(&self, image: &mut DynamicImage) {
-        match &self.coordinate {
-            Coordinate::Point { x, y } => {
-                image.put_pixel(*x as u32, *y as u32, self.pixel_color);
-            }
-            Coordinate::Circle { x, y, r } => {
-                todo!()
-            }
-            Coordinate::Rectangle { x1, x2, y1, y2 } => {
-                todo!()
-            }
-        }
+    pub fn get_timestamp(&self) -> NaiveDateTime {
+        self.timestamp
+    }
+
+    pub fn get_user(&self) -> &str {
+        &self.user
+    }
+
+    pub fn get_coordinate(&self) -> &Coordinate {
+        &self.coordinate
+    }
+
+    pub fn get_pixel_color(&self) -> Rgba<u8> {
+        self.pixel_color
+    }
+
+    /// Draws the record on an image covering `region`, pixels outside of the region are skipped.
+    pub fn draw_on_image(&self, image: &mut RgbaImage, region: &CanvasRegion) {
+        self.coordinate.for_each_pixel(region, |x, y| {
+            image.put_pixel(
+                (x - region.x) as u32,
+                (y - region.y) as u32,
+                self.pixel_color,
+            );
+        });
     }
 }
 
 #[derive(Debug, serde_derive::Deserialize, PartialEq)]
 #[serde(untagged)]
-enum Coordinate {
+pub enum Coordinate {
     Point { x: i32, y: i32 },
     Circle { x: i32, y: i32, r: i32 },
     Rectangle { x1: i32, x2: i32, y1: i32, y2: i32 },
 }
 
+impl Coordinate {
+    pub fn bounding_region(&self) -> CanvasRegion {
+        match *self {
+            Coordinate::Point { x, y } => CanvasRegion::new(x, y, 1, 1),
+            Coordinate::Circle { x, y, r } => {
+                let r = r.abs();
+                CanvasRegion::from_corners(x - r, y - r, x + r, y + r)
+            }
+            Coordinate::Rectangle { x1, x2, y1, y2 } => {
+                CanvasRegion::from_corners(x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2))
+            }
+        }
+    }
+
+    pub fn covers(&self, pixel_x: i32, pixel_y: i32) -> bool {
+        match *self {
+            Coordinate::Point { x, y } => x == pixel_x && y == pixel_y,
+            Coordinate::Circle { x, y, r } => {
+                let delta_x = (pixel_x - x) as i64;
+                let delta_y = (pixel_y - y) as i64;
+                let r = r as i64;
+
+                delta_x * delta_x + delta_y * delta_y <= r * r
+            }
+            Coordinate::Rectangle { .. } => self.bounding_region().contains(pixel_x, pixel_y),
+        }
+    }
+
+    /// Calls `f` with every canvas coordinate covered by the placement that lies inside `region`.
+    pub fn for_each_pixel<F>(&self, region: &CanvasRegion, mut f: F)
+    where
+        F: FnMut(i32, i32),
+    {
+        let clipped_region = match self.bounding_region().intersection(region) {
+            Some(clipped_region) => clipped_region,
+            None => return,
+        };
+
+        for y in clipped_region.y..=clipped_region.max_y() {
+            for x in clipped_region.x..=clipped_region.max_x() {
+                if self.covers(x, y) {
+                    f(x, y);
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod csv_parsing_tests {
     use super::{CSVRecord, Coordinate};
@@ -139,6 +202,23 @@ mod csv_parsing_tests {
         assert_eq!(record.pixel_color, expected_pixel_color);
     }
 
+    #[test]
+    fn test_parse_timestamp_with_timezone() {
+        let csv_data = "timestamp,user,coordinate,pixel_color
+        2023-07-20 13:00:26.088 UTC,user,\"-199,-235\",#FFFFFF";
+
+        let mut reader = ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(Cursor::new(csv_data));
+
+        let record: CSVRecord = reader.deserialize().next().unwrap().unwrap();
+        let expected_timestamp =
+            NaiveDateTime::parse_from_str("2023-07-20 13:00:26.088", "%Y-%m-%d %H:%M:%S%.f")
+                .unwrap();
+
+        assert_eq!(record.timestamp, expected_timestamp);
+    }
+
     #[test]
     fn test_parse_point_coordinate() {
         let csv_data = "timestamp,user,coordinate,pixel_color
@@ -237,3 +317,42 @@ mod hex_parsing_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod coordinate_tests {
+    use super::{CanvasRegion, Coordinate};
+
+    fn collect_pixels(coordinate: &Coordinate, region: &CanvasRegion) -> Vec<(i32, i32)> {
+        let mut pixels = Vec::new();
+        coordinate.for_each_pixel(region, |x, y| pixels.push((x, y)));
+        pixels
+    }
+
+    #[test]
+    fn test_point_outside_region() {
+        let coordinate = Coordinate::Point { x: -5, y: 3 };
+
+        assert!(collect_pixels(&coordinate, &CanvasRegion::new(0, 0, 10, 10)).is_empty());
+    }
+
+    #[test]
+    fn test_circle_pixels() {
+        let coordinate = Coordinate::Circle { x: 0, y: 0, r: 1 };
+        let pixels = collect_pixels(&coordinate, &CanvasRegion::new(-5, -5, 10, 10));
+
+        assert_eq!(pixels, vec![(0, -1), (-1, 0), (0, 0), (1, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn test_rectangle_clipped_to_region() {
+        let coordinate = Coordinate::Rectangle {
+            x1: 3,
+            x2: -2,
+            y1: 0,
+            y2: 1,
+        };
+        let pixels = collect_pixels(&coordinate, &CanvasRegion::new(0, 1, 2, 2));
+
+        assert_eq!(pixels, vec![(0, 1), (1, 1)]);
+    }
+}
@@ -1,3 +1,63 @@
+use std::error::Error;
+
+use csv::ReaderBuilder;
+
+use super::CSVRecord;
+
 pub struct FeedParser {}
 
-impl FeedParser {}
+impl FeedParser {
+    pub fn parse_file(path: &str) -> Result<Vec<CSVRecord>, Box<dyn Error>> {
+        let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
+
+        let mut records = Vec::new();
+
+        for record in reader.deserialize() {
+            records.push(record?);
+        }
+
+        Ok(records)
+    }
+
+    /// Parses all files and returns their records ordered by timestamp.
+    pub fn parse_files(paths: Vec<&str>) -> Result<Vec<CSVRecord>, Box<dyn Error>> {
+        let mut records = Vec::new();
+
+        for path in paths {
+            records.extend(FeedParser::parse_file(path)?);
+        }
+
+        records.sort_by_key(|record| record.get_timestamp());
+
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FeedParser;
+
+    #[test]
+    fn test_parse_file() {
+        let records = FeedParser::parse_file(
+            "assets/rplaceSample/2023_place_canvas_history-000000000000.csv",
+        )
+        .unwrap();
+
+        assert_eq!(records.len(), 999);
+    }
+
+    #[test]
+    fn test_parse_files_sorted() {
+        let records = FeedParser::parse_files(vec![
+            "assets/rplaceSample/2023_place_canvas_history-000000000001.csv",
+            "assets/rplaceSample/2023_place_canvas_history-000000000000.csv",
+        ])
+        .unwrap();
+
+        assert_eq!(records.len(), 1998);
+        assert!(records
+            .windows(2)
+            .all(|pair| pair[0].get_timestamp() <= pair[1].get_timestamp()));
+    }
+}
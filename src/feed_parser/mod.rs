@@ -1,4 +1,9 @@
+pub use canvas_region::CanvasRegion;
+pub use csv_record::{CSVRecord, Coordinate};
+pub use feed_parser::FeedParser;
+pub use user_interner::UserInterner;
+
+mod canvas_region;
 mod csv_record;
 mod feed_parser;
-
-pub use feed_parser::FeedParser;
+mod user_interner;
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+/// Maps the long hashed user names of the feed to compact sequential ids.
+#[derive(Debug, Default)]
+pub struct UserInterner {
+    ids: HashMap<String, u32>,
+    users: Vec<String>,
+}
+
+impl UserInterner {
+    pub fn new() -> Self {
+        UserInterner::default()
+    }
+
+    pub fn intern(&mut self, user: &str) -> u32 {
+        if let Some(id) = self.ids.get(user) {
+            return *id;
+        }
+
+        let id = self.users.len() as u32;
+        self.users.push(user.to_string());
+        self.ids.insert(user.to_string(), id);
+
+        id
+    }
+
+    pub fn get_id(&self, user: &str) -> Option<u32> {
+        self.ids.get(user).copied()
+    }
+
+    pub fn get_user(&self, id: u32) -> Option<&str> {
+        self.users.get(id as usize).map(|user| user.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserInterner;
+
+    #[test]
+    fn test_intern_reuses_ids() {
+        let mut interner = UserInterner::new();
+
+        let first_id = interner.intern("first");
+        let second_id = interner.intern("second");
+
+        assert_eq!(interner.intern("first"), first_id);
+        assert_ne!(first_id, second_id);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.get_user(second_id), Some("second"));
+        assert_eq!(interner.get_id("third"), None);
+    }
+}
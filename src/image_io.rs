@@ -1,5 +1,7 @@
 use image::{io::Reader as ImageReader, DynamicImage};
-use std::{error::Error, path::Path};
+use std::{error::Error, fs, path::Path};
+
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "bmp"];
 
 pub struct ImageIO {}
 
@@ -9,18 +11,47 @@ impl ImageIO {
         Ok(search_img)
     }
 
-    pub fn load_multiple_images(paths: Vec<&str>) -> Result<Vec<DynamicImage>, Box<dyn Error>> {
-        let mut images = Vec::new();
+    pub fn save_image(image: &DynamicImage, path: &str) -> Result<(), Box<dyn Error>> {
+        image.save(Path::new(path))?;
+        Ok(())
+    }
 
-        for path in paths {
-            if ImageIO::is_path_valid(path) {
-                if let Ok(image) = ImageIO::load_image(path) {
-                    images.push(image);
+    /// Loads every image, the result of each path is reported separately and in the same order.
+    pub fn load_multiple_images(paths: Vec<&str>) -> Vec<Result<DynamicImage, Box<dyn Error>>> {
+        paths
+            .into_iter()
+            .map(|path| {
+                if ImageIO::is_path_valid(path) {
+                    ImageIO::load_image(path)
+                } else {
+                    Err(format!("Path does not exist: {}", path).into())
                 }
+            })
+            .collect()
+    }
+
+    /// Paths of all image files directly inside `directory`, sorted by name.
+    pub fn list_images_in_directory(directory: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut paths = Vec::new();
+
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+
+            let is_image = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+                });
+
+            if path.is_file() && is_image {
+                paths.push(path.to_string_lossy().into_owned());
             }
         }
 
-        Ok(images)
+        paths.sort();
+
+        Ok(paths)
     }
 
     pub fn is_path_valid(path: &str) -> bool {
@@ -31,3 +62,44 @@ impl ImageIO {
         paths.iter().all(|&path| ImageIO::is_path_valid(path))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ImageIO;
+    use crate::test_utils::TempDirectory;
+    use std::fs;
+
+    #[test]
+    fn test_load_multiple_images_reports_failures() {
+        let results = ImageIO::load_multiple_images(vec![
+            "assets/images/crewmate.png",
+            "assets/images/missing.png",
+            "assets/rplaceSample/2023_place_canvas_history-000000000000.csv",
+        ]);
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn test_list_images_in_directory() {
+        let directory = TempDirectory::new("list_images");
+        for file_name in ["b.png", "a.JPG", "notes.txt"] {
+            fs::write(directory.join(file_name), b"").unwrap();
+        }
+        fs::create_dir(directory.join("nested.png")).unwrap();
+
+        let paths =
+            ImageIO::list_images_in_directory(directory.get_path().to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![directory.join("a.JPG"), directory.join("b.png")]
+        );
+        assert!(ImageIO::list_images_in_directory("assets/rplaceSample")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use super::coordinate::Coordinate;
+
+/// Inclusive rectangle spanned by a set of coordinates.
+#[derive(
+    Debug, PartialEq, Eq, Hash, Copy, Clone, serde_derive::Serialize, serde_derive::Deserialize,
+)]
+pub struct BoundingBox {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl BoundingBox {
+    pub fn new(min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Self {
+        BoundingBox {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    pub fn from_coordinate(coordinate: &Coordinate) -> Self {
+        BoundingBox::new(coordinate.x, coordinate.y, coordinate.x, coordinate.y)
+    }
+
+    pub fn from_coordinates<'a, I>(coordinates: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Coordinate>,
+    {
+        let mut coordinates = coordinates.into_iter();
+        let mut bounding_box = BoundingBox::from_coordinate(coordinates.next()?);
+
+        for coordinate in coordinates {
+            bounding_box.include(coordinate);
+        }
+
+        Some(bounding_box)
+    }
+
+    pub fn include(&mut self, coordinate: &Coordinate) {
+        self.min_x = self.min_x.min(coordinate.x);
+        self.min_y = self.min_y.min(coordinate.y);
+        self.max_x = self.max_x.max(coordinate.x);
+        self.max_y = self.max_y.max(coordinate.y);
+    }
+
+    pub fn width(&self) -> u32 {
+        (self.max_x - self.min_x + 1) as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        (self.max_y - self.min_y + 1) as u32
+    }
+
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        coordinate.x >= self.min_x
+            && coordinate.x <= self.max_x
+            && coordinate.y >= self.min_y
+            && coordinate.y <= self.max_y
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoundingBox, Coordinate};
+
+    #[test]
+    fn test_from_coordinates() {
+        let coordinates = vec![
+            Coordinate { x: 2, y: 5 },
+            Coordinate { x: 4, y: 3 },
+            Coordinate { x: 1, y: 6 },
+        ];
+
+        let bounding_box = BoundingBox::from_coordinates(&coordinates).unwrap();
+
+        assert_eq!(bounding_box, BoundingBox::new(1, 3, 4, 6));
+        assert_eq!((bounding_box.width(), bounding_box.height()), (4, 4));
+        assert_eq!(BoundingBox::from_coordinates(&Vec::new()), None);
+    }
+
+    #[test]
+    fn test_intersects() {
+        let bounding_box = BoundingBox::new(0, 0, 3, 3);
+
+        assert!(bounding_box.intersects(&BoundingBox::new(3, 3, 5, 5)));
+        assert!(!bounding_box.intersects(&BoundingBox::new(4, 0, 5, 3)));
+    }
+}
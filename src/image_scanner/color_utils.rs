@@ -1,22 +1,310 @@
+use std::{cell::RefCell, collections::HashMap};
+
 use image::Rgba;
 
+/// Colors of the 2023 r/place palette.
+pub const PLACE_2023_PALETTE: [Rgba<u8>; 32] = [
+    Rgba([109, 0, 26, 255]),
+    Rgba([190, 0, 57, 255]),
+    Rgba([255, 69, 0, 255]),
+    Rgba([255, 168, 0, 255]),
+    Rgba([255, 214, 53, 255]),
+    Rgba([255, 248, 184, 255]),
+    Rgba([0, 163, 104, 255]),
+    Rgba([0, 204, 120, 255]),
+    Rgba([126, 237, 86, 255]),
+    Rgba([0, 117, 111, 255]),
+    Rgba([0, 158, 170, 255]),
+    Rgba([0, 204, 192, 255]),
+    Rgba([36, 80, 164, 255]),
+    Rgba([54, 144, 234, 255]),
+    Rgba([81, 233, 244, 255]),
+    Rgba([73, 58, 193, 255]),
+    Rgba([106, 92, 255, 255]),
+    Rgba([148, 179, 255, 255]),
+    Rgba([129, 30, 159, 255]),
+    Rgba([180, 74, 192, 255]),
+    Rgba([228, 171, 255, 255]),
+    Rgba([222, 16, 127, 255]),
+    Rgba([255, 56, 129, 255]),
+    Rgba([255, 153, 170, 255]),
+    Rgba([109, 72, 47, 255]),
+    Rgba([156, 105, 38, 255]),
+    Rgba([255, 180, 112, 255]),
+    Rgba([0, 0, 0, 255]),
+    Rgba([81, 82, 82, 255]),
+    Rgba([137, 141, 144, 255]),
+    Rgba([212, 215, 217, 255]),
+    Rgba([255, 255, 255, 255]),
+];
+
+/// Colors kept in the palette index cache of a thread before it is cleared.
+const PALETTE_INDEX_CACHE_SIZE: usize = 4096;
+
+thread_local! {
+    /// Palette indices of recently compared RGB values, scanned images hold few distinct colors.
+    static PALETTE_INDEX_CACHE: RefCell<HashMap<[u8; 3], usize>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde_derive::Serialize)]
+pub enum ColorMetric {
+    /// Every RGB channel may differ by at most the tolerance.
+    ChannelDelta,
+    /// Euclidean distance in RGB space.
+    EuclideanRgb,
+    /// CIE76 distance in Lab space.
+    DeltaE76,
+    /// CIEDE2000 distance in Lab space.
+    DeltaE2000,
+    /// Both colors snap to the same color of the 2023 palette, the tolerance is not used.
+    PaletteIndex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde_derive::Serialize)]
+pub enum AlphaHandling {
+    /// Only RGB channels are compared.
+    Ignore,
+    /// Alpha channels may differ by at most `tolerance`, fully transparent colors
+    /// are equal regardless of their RGB channels.
+    Compare { tolerance: u8 },
+}
+
+/// Decides whether two colors are equal under a metric and tolerance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorMatcher {
+    pub metric: ColorMetric,
+    /// Largest accepted distance in the units of the metric, fractions are kept for
+    /// the distance based metrics.
+    pub tolerance: f32,
+    pub alpha_handling: AlphaHandling,
+}
+
+impl ColorMatcher {
+    pub fn new(metric: ColorMetric, tolerance: f32, alpha_handling: AlphaHandling) -> Self {
+        ColorMatcher {
+            metric,
+            tolerance,
+            alpha_handling,
+        }
+    }
+
+    pub fn matches(&self, color1: Rgba<u8>, color2: Rgba<u8>) -> bool {
+        if let AlphaHandling::Compare { tolerance } = self.alpha_handling {
+            if color1.0[3] == 0 && color2.0[3] == 0 {
+                return true;
+            }
+            if color1.0[3].abs_diff(color2.0[3]) > tolerance {
+                return false;
+            }
+        }
+
+        let tolerance = self.tolerance as f64;
+
+        match self.metric {
+            // Channel deltas are whole numbers, so the fraction of the tolerance never matters.
+            ColorMetric::ChannelDelta => ColorUtils::equal_with_tolerance(
+                color1,
+                color2,
+                self.tolerance.clamp(0.0, 255.0) as u8,
+            ),
+            ColorMetric::EuclideanRgb => {
+                ColorUtils::euclidean_distance(color1, color2) <= tolerance
+            }
+            ColorMetric::DeltaE76 => ColorUtils::delta_e76(color1, color2) <= tolerance,
+            ColorMetric::DeltaE2000 => ColorUtils::delta_e2000(color1, color2) <= tolerance,
+            ColorMetric::PaletteIndex => {
+                ColorUtils::cached_palette_index(color1) == ColorUtils::cached_palette_index(color2)
+            }
+        }
+    }
+}
+
 pub struct ColorUtils;
 
 impl ColorUtils {
     pub fn equal_with_tolerance(color1: Rgba<u8>, color2: Rgba<u8>, tolerance: u8) -> bool {
-        let delta_r = (color1.0[0] as i16 - color2.0[0] as i16).abs() as u8;
-        let delta_g = (color1.0[1] as i16 - color2.0[1] as i16).abs() as u8;
-        let delta_b = (color1.0[2] as i16 - color2.0[2] as i16).abs() as u8;
+        let delta_r = color1.0[0].abs_diff(color2.0[0]);
+        let delta_g = color1.0[1].abs_diff(color2.0[1]);
+        let delta_b = color1.0[2].abs_diff(color2.0[2]);
 
         delta_r <= tolerance && delta_g <= tolerance && delta_b <= tolerance
     }
+
+    pub fn euclidean_distance(color1: Rgba<u8>, color2: Rgba<u8>) -> f64 {
+        (0..3)
+            .map(|channel| (color1.0[channel] as f64 - color2.0[channel] as f64).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Converts sRGB to CIE Lab with a D65 white point.
+    pub fn to_lab(color: Rgba<u8>) -> (f64, f64, f64) {
+        let linear = |channel: u8| {
+            let value = channel as f64 / 255.0;
+            if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        let (r, g, b) = (linear(color.0[0]), linear(color.0[1]), linear(color.0[2]));
+
+        let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+        let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+        let z = (0.0193339 * r + 0.1191920 * g + 0.9503041 * b) / 1.08883;
+
+        let f = |t: f64| {
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                (24389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        };
+
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+
+        (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+    }
+
+    pub fn delta_e76(color1: Rgba<u8>, color2: Rgba<u8>) -> f64 {
+        let (l1, a1, b1) = ColorUtils::to_lab(color1);
+        let (l2, a2, b2) = ColorUtils::to_lab(color2);
+
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    pub fn delta_e2000(color1: Rgba<u8>, color2: Rgba<u8>) -> f64 {
+        ColorUtils::delta_e2000_lab(ColorUtils::to_lab(color1), ColorUtils::to_lab(color2))
+    }
+
+    pub fn delta_e2000_lab(lab1: (f64, f64, f64), lab2: (f64, f64, f64)) -> f64 {
+        let (l1, a1, b1) = lab1;
+        let (l2, a2, b2) = lab2;
+
+        let c1 = (a1 * a1 + b1 * b1).sqrt();
+        let c2 = (a2 * a2 + b2 * b2).sqrt();
+        let c_mean_pow7 = ((c1 + c2) / 2.0).powi(7);
+        let g = 0.5 * (1.0 - (c_mean_pow7 / (c_mean_pow7 + 25f64.powi(7))).sqrt());
+
+        let a1_prime = a1 * (1.0 + g);
+        let a2_prime = a2 * (1.0 + g);
+        let c1_prime = (a1_prime * a1_prime + b1 * b1).sqrt();
+        let c2_prime = (a2_prime * a2_prime + b2 * b2).sqrt();
+
+        let hue = |b: f64, a_prime: f64| {
+            if b == 0.0 && a_prime == 0.0 {
+                0.0
+            } else {
+                b.atan2(a_prime).to_degrees().rem_euclid(360.0)
+            }
+        };
+        let h1_prime = hue(b1, a1_prime);
+        let h2_prime = hue(b2, a2_prime);
+
+        let delta_l_prime = l2 - l1;
+        let delta_c_prime = c2_prime - c1_prime;
+
+        let delta_h_angle = if c1_prime * c2_prime == 0.0 {
+            0.0
+        } else if (h2_prime - h1_prime).abs() <= 180.0 {
+            h2_prime - h1_prime
+        } else if h2_prime - h1_prime > 180.0 {
+            h2_prime - h1_prime - 360.0
+        } else {
+            h2_prime - h1_prime + 360.0
+        };
+        let delta_h_prime =
+            2.0 * (c1_prime * c2_prime).sqrt() * (delta_h_angle.to_radians() / 2.0).sin();
+
+        let l_mean_prime = (l1 + l2) / 2.0;
+        let c_mean_prime = (c1_prime + c2_prime) / 2.0;
+
+        let h_mean_prime = if c1_prime * c2_prime == 0.0 {
+            h1_prime + h2_prime
+        } else if (h1_prime - h2_prime).abs() <= 180.0 {
+            (h1_prime + h2_prime) / 2.0
+        } else if h1_prime + h2_prime < 360.0 {
+            (h1_prime + h2_prime + 360.0) / 2.0
+        } else {
+            (h1_prime + h2_prime - 360.0) / 2.0
+        };
+
+        let t = 1.0 - 0.17 * (h_mean_prime - 30.0).to_radians().cos()
+            + 0.24 * (2.0 * h_mean_prime).to_radians().cos()
+            + 0.32 * (3.0 * h_mean_prime + 6.0).to_radians().cos()
+            - 0.20 * (4.0 * h_mean_prime - 63.0).to_radians().cos();
+
+        let delta_theta = 30.0 * (-((h_mean_prime - 275.0) / 25.0).powi(2)).exp();
+        let c_mean_prime_pow7 = c_mean_prime.powi(7);
+        let r_c = 2.0 * (c_mean_prime_pow7 / (c_mean_prime_pow7 + 25f64.powi(7))).sqrt();
+        let s_l = 1.0
+            + (0.015 * (l_mean_prime - 50.0).powi(2))
+                / (20.0 + (l_mean_prime - 50.0).powi(2)).sqrt();
+        let s_c = 1.0 + 0.045 * c_mean_prime;
+        let s_h = 1.0 + 0.015 * c_mean_prime * t;
+        let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+        let l_term = delta_l_prime / s_l;
+        let c_term = delta_c_prime / s_c;
+        let h_term = delta_h_prime / s_h;
+
+        (l_term * l_term + c_term * c_term + h_term * h_term + r_t * c_term * h_term).sqrt()
+    }
+
+    /// Index of the closest color of the 2023 palette.
+    pub fn palette_index(color: Rgba<u8>) -> usize {
+        PLACE_2023_PALETTE
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, palette_color)| {
+                (0..3)
+                    .map(|channel| {
+                        (color.0[channel] as i32 - palette_color.0[channel] as i32).pow(2)
+                    })
+                    .sum::<i32>()
+            })
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+
+    /// Same as `palette_index`, but every RGB value is only searched once per thread.
+    pub fn cached_palette_index(color: Rgba<u8>) -> usize {
+        let rgb = [color.0[0], color.0[1], color.0[2]];
+
+        PALETTE_INDEX_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+
+            if let Some(index) = cache.get(&rgb) {
+                return *index;
+            }
+            if cache.len() >= PALETTE_INDEX_CACHE_SIZE {
+                cache.clear();
+            }
+
+            *cache
+                .entry(rgb)
+                .or_insert_with(|| ColorUtils::palette_index(color))
+        })
+    }
+
+    /// `#RRGGBB`, or `#RRGGBBAA` for colors that are not fully opaque.
+    pub fn to_hex(color: Rgba<u8>) -> String {
+        let [r, g, b, a] = color.0;
+
+        if a == 255 {
+            format!("#{:02X}{:02X}{:02X}", r, g, b)
+        } else {
+            format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use image::Rgba;
 
-    use super::ColorUtils;
+    use super::{AlphaHandling, ColorMatcher, ColorMetric, ColorUtils};
 
     #[test]
     fn test_equal_with_tolerance_exact_match() {
@@ -44,4 +332,97 @@ mod tests {
 
         assert!(!ColorUtils::equal_with_tolerance(color1, color2, tolerance));
     }
+
+    #[test]
+    fn test_euclidean_distance() {
+        let color1 = Rgba([0, 0, 0, 255]);
+        let color2 = Rgba([3, 4, 0, 255]);
+
+        assert_eq!(ColorUtils::euclidean_distance(color1, color2), 5.0);
+    }
+
+    #[test]
+    fn test_delta_e2000_reference_pairs() {
+        // Pairs 1 and 7 of the CIEDE2000 test data by Sharma, Wu and Dalal.
+        let pair_1 = ColorUtils::delta_e2000_lab((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485));
+        let pair_7 = ColorUtils::delta_e2000_lab((50.0, 0.0, 0.0), (50.0, -1.0, 2.0));
+
+        assert!((pair_1 - 2.0425).abs() < 1e-4);
+        assert!((pair_7 - 2.3669).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_to_lab() {
+        let (l, a, b) = ColorUtils::to_lab(Rgba([255, 255, 255, 255]));
+
+        assert!((l - 100.0).abs() < 0.01);
+        assert!(a.abs() < 0.01);
+        assert!(b.abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fractional_tolerance() {
+        let color1 = Rgba([0, 0, 0, 255]);
+        let color2 = Rgba([1, 1, 0, 255]);
+
+        assert!(
+            ColorMatcher::new(ColorMetric::EuclideanRgb, 1.5, AlphaHandling::Ignore)
+                .matches(color1, color2)
+        );
+        assert!(
+            !ColorMatcher::new(ColorMetric::EuclideanRgb, 1.4, AlphaHandling::Ignore)
+                .matches(color1, color2)
+        );
+        assert!(
+            ColorMatcher::new(ColorMetric::ChannelDelta, 1.5, AlphaHandling::Ignore)
+                .matches(color1, color2)
+        );
+    }
+
+    #[test]
+    fn test_cached_palette_index() {
+        for color in [
+            Rgba([255, 70, 3, 255]),
+            Rgba([255, 70, 3, 0]),
+            Rgba([130, 130, 130, 255]),
+        ] {
+            assert_eq!(
+                ColorUtils::cached_palette_index(color),
+                ColorUtils::palette_index(color)
+            );
+            assert_eq!(
+                ColorUtils::cached_palette_index(color),
+                ColorUtils::palette_index(color)
+            );
+        }
+        assert_eq!(ColorUtils::palette_index(Rgba([255, 70, 3, 255])), 2);
+    }
+
+    #[test]
+    fn test_palette_index_matcher() {
+        let matcher = ColorMatcher::new(ColorMetric::PaletteIndex, 0.0, AlphaHandling::Ignore);
+
+        assert!(matcher.matches(Rgba([255, 70, 3, 255]), Rgba([250, 65, 0, 255])));
+        assert!(!matcher.matches(Rgba([255, 70, 3, 255]), Rgba([255, 168, 0, 255])));
+    }
+
+    #[test]
+    fn test_alpha_handling() {
+        let ignoring = ColorMatcher::new(ColorMetric::ChannelDelta, 0.0, AlphaHandling::Ignore);
+        let comparing = ColorMatcher::new(
+            ColorMetric::ChannelDelta,
+            0.0,
+            AlphaHandling::Compare { tolerance: 0 },
+        );
+
+        assert!(ignoring.matches(Rgba([0, 0, 0, 0]), Rgba([0, 0, 0, 255])));
+        assert!(!comparing.matches(Rgba([0, 0, 0, 0]), Rgba([0, 0, 0, 255])));
+        assert!(comparing.matches(Rgba([10, 0, 0, 0]), Rgba([0, 0, 0, 0])));
+    }
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(ColorUtils::to_hex(Rgba([255, 69, 0, 255])), "#FF4500");
+        assert_eq!(ColorUtils::to_hex(Rgba([0, 0, 0, 0])), "#00000000");
+    }
 }
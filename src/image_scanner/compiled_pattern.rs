@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+
+use image::Rgba;
+
+use super::{
+    color_utils::ColorMatcher, coordinate::Coordinate, neighborhood::BorderRule, pattern::Pattern,
+};
+
+const BITS_PER_WORD: u32 = u64::BITS;
+
+/// Cells of one window row, rows above and below the window only hold border cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CompiledRow {
+    y: i32,
+    /// Bitmask of the pattern cells, 64 cells per word.
+    pattern_mask: Vec<u64>,
+    border_offsets: Vec<i32>,
+}
+
+impl CompiledRow {
+    fn entry(rows: &mut BTreeMap<i32, CompiledRow>, y: i32, words_per_row: usize) -> &mut Self {
+        rows.entry(y).or_insert_with(|| CompiledRow {
+            y,
+            pattern_mask: vec![0; words_per_row],
+            border_offsets: Vec::new(),
+        })
+    }
+}
+
+/// Pattern prepared for scanning a raw RGBA buffer, everything that only depends on
+/// the pattern is computed once instead of for every window.
+#[derive(Debug, Clone)]
+pub struct CompiledPattern {
+    pattern: Pattern,
+    window_width: u32,
+    window_height: u32,
+    /// Cell checked first, its color is the color every other cell is compared to.
+    reference_offset: Option<(u32, u32)>,
+    /// Rows ordered from top to bottom, pattern and border cells of a row are checked
+    /// together so uniform areas are rejected by the first border row.
+    rows: Vec<CompiledRow>,
+}
+
+impl CompiledPattern {
+    /// The pattern is normalized, found patterns are placed relative to its bounding box.
+    pub fn new(pattern: &Pattern, border_rule: &BorderRule) -> Self {
+        let pattern = &pattern.normalized();
+        let (window_width, window_height) = pattern.get_window_size();
+        let words_per_row = window_width.div_ceil(BITS_PER_WORD) as usize;
+
+        let mut rows: BTreeMap<i32, CompiledRow> = BTreeMap::new();
+        for coordinate in pattern.get_coordinates() {
+            let x = coordinate.x as u32;
+
+            CompiledRow::entry(&mut rows, coordinate.y, words_per_row).pattern_mask
+                [(x / BITS_PER_WORD) as usize] |= 1 << (x % BITS_PER_WORD);
+        }
+
+        for coordinate in pattern.generate_coordinates_of_adjacent_pixels(border_rule) {
+            CompiledRow::entry(&mut rows, coordinate.y, words_per_row)
+                .border_offsets
+                .push(coordinate.x);
+        }
+
+        let mut rows: Vec<CompiledRow> = rows.into_values().collect();
+        for row in &mut rows {
+            row.border_offsets.sort_unstable();
+        }
+
+        CompiledPattern {
+            pattern: pattern.clone(),
+            window_width,
+            window_height,
+            reference_offset: pattern
+                .get_coordinates()
+                .first()
+                .map(|coordinate| (coordinate.x as u32, coordinate.y as u32)),
+            rows,
+        }
+    }
+
+    pub fn get_pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    pub fn get_window_size(&self) -> (u32, u32) {
+        (self.window_width, self.window_height)
+    }
+
+    /// Checks the window with its top-left corner at (`offset_x`, `offset_y`) of a raw
+    /// RGBA buffer, the window has to fit into the image. Rows are checked top to bottom
+    /// and the first mismatching cell rejects the window.
+    pub fn matches_at(
+        &self,
+        pixels: &[u8],
+        image_size: (u32, u32),
+        offset_x: u32,
+        offset_y: u32,
+        color_matcher: &ColorMatcher,
+    ) -> bool {
+        let (img_width, img_height) = image_size;
+        let pixel_at = |x: u32, y: u32| pixel_at(pixels, img_width, x, y);
+
+        let reference_color = match self.reference_offset {
+            Some((x, y)) => pixel_at(offset_x + x, offset_y + y),
+            None => return false,
+        };
+
+        for row in &self.rows {
+            let y = offset_y as i32 + row.y;
+
+            if y < 0 || y >= img_height as i32 {
+                continue;
+            }
+
+            for border_x in &row.border_offsets {
+                let x = offset_x as i32 + border_x;
+
+                if x >= 0
+                    && x < img_width as i32
+                    && color_matcher.matches(reference_color, pixel_at(x as u32, y as u32))
+                {
+                    return false;
+                }
+            }
+
+            for (word_index, word) in row.pattern_mask.iter().enumerate() {
+                let mut bits = *word;
+
+                while bits != 0 {
+                    let x = offset_x + word_index as u32 * BITS_PER_WORD + bits.trailing_zeros();
+                    bits &= bits - 1;
+
+                    if !color_matcher.matches(reference_color, pixel_at(x, y as u32)) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Like `matches_at` for windows that stick out of the image, only cells inside the
+    /// image are checked and the first visible pattern cell is the reference color.
+    /// Returns the visible pattern cells when at least `min_visible_ratio` of them is visible.
+    pub fn clipped_match_at(
+        &self,
+        pixels: &[u8],
+        image_size: (u32, u32),
+        offset_x: i32,
+        offset_y: i32,
+        color_matcher: &ColorMatcher,
+        min_visible_ratio: f32,
+    ) -> Option<Pattern> {
+        let (img_width, img_height) = image_size;
+        let is_inside =
+            |x: i32, y: i32| x >= 0 && y >= 0 && (x as u32) < img_width && (y as u32) < img_height;
+        let pixel_at = |x: i32, y: i32| pixel_at(pixels, img_width, x as u32, y as u32);
+
+        let visible_coordinates: Vec<Coordinate> = self
+            .pattern
+            .get_coordinates()
+            .iter()
+            .map(|coordinate| Coordinate::new(coordinate.x + offset_x, coordinate.y + offset_y))
+            .filter(|coordinate| is_inside(coordinate.x, coordinate.y))
+            .collect();
+
+        let visible_ratio =
+            visible_coordinates.len() as f32 / self.pattern.get_coordinates().len() as f32;
+
+        if visible_coordinates.is_empty() || visible_ratio < min_visible_ratio {
+            return None;
+        }
+
+        let reference_color = pixel_at(visible_coordinates[0].x, visible_coordinates[0].y);
+
+        let pattern_matches = visible_coordinates.iter().all(|coordinate| {
+            color_matcher.matches(reference_color, pixel_at(coordinate.x, coordinate.y))
+        });
+        if !pattern_matches {
+            return None;
+        }
+
+        for row in &self.rows {
+            let y = offset_y + row.y;
+
+            for border_x in &row.border_offsets {
+                let x = offset_x + border_x;
+
+                if is_inside(x, y) && color_matcher.matches(reference_color, pixel_at(x, y)) {
+                    return None;
+                }
+            }
+        }
+
+        Some(Pattern::new_with_variant(
+            visible_coordinates,
+            self.pattern.get_variant(),
+        ))
+    }
+
+    /// Pattern cells moved to the window at (`offset_x`, `offset_y`).
+    pub fn found_pattern_at(&self, offset_x: u32, offset_y: u32) -> Pattern {
+        let coordinates = self
+            .pattern
+            .get_coordinates()
+            .iter()
+            .map(|coordinate| {
+                Coordinate::new(
+                    coordinate.x + offset_x as i32,
+                    coordinate.y + offset_y as i32,
+                )
+            })
+            .collect();
+
+        Pattern::new_with_variant(coordinates, self.pattern.get_variant())
+    }
+}
+
+fn pixel_at(pixels: &[u8], img_width: u32, x: u32, y: u32) -> Rgba<u8> {
+    let index = (y as usize * img_width as usize + x as usize) * 4;
+
+    Rgba([
+        pixels[index],
+        pixels[index + 1],
+        pixels[index + 2],
+        pixels[index + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::CompiledPattern;
+    use crate::image_scanner::coordinate::Coordinate;
+    use crate::image_scanner::neighborhood::BorderRule;
+    use crate::image_scanner::pattern::Pattern;
+    use crate::test_utils::COLOR_MATCHER;
+
+    #[test]
+    fn test_row_masks_of_wide_pattern() {
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 70, y: 0 },
+            Coordinate { x: 3, y: 2 },
+        ]);
+
+        let compiled = CompiledPattern::new(&pattern, &BorderRule::new_default());
+
+        let pattern_masks: Vec<(i32, Vec<u64>)> = compiled
+            .rows
+            .iter()
+            .map(|row| (row.y, row.pattern_mask.clone()))
+            .collect();
+
+        assert_eq!(compiled.get_window_size(), (71, 3));
+        assert_eq!(
+            pattern_masks,
+            vec![
+                (-1, vec![0, 0]),
+                (0, vec![1, 1 << 6]),
+                (1, vec![0, 0]),
+                (2, vec![1 << 3, 0]),
+                (3, vec![0, 0]),
+            ]
+        );
+        assert_eq!(compiled.rows[0].border_offsets, vec![-1, 0, 1, 69, 70, 71]);
+    }
+
+    #[test]
+    fn test_matches_at() {
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 1 },
+        ]);
+        let mut image = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        image.put_pixel(1, 1, Rgba([0, 0, 0, 255]));
+        image.put_pixel(2, 2, Rgba([0, 0, 0, 255]));
+
+        let compiled = CompiledPattern::new(&pattern, &BorderRule::new_default());
+        let size = image.dimensions();
+
+        assert!(compiled.matches_at(image.as_raw(), size, 1, 1, &COLOR_MATCHER));
+        assert!(!compiled.matches_at(image.as_raw(), size, 0, 0, &COLOR_MATCHER));
+        assert_eq!(
+            compiled.found_pattern_at(1, 1).get_coordinates(),
+            &vec![Coordinate { x: 1, y: 1 }, Coordinate { x: 2, y: 2 }]
+        );
+    }
+}
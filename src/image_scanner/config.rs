@@ -1,29 +1,97 @@
 use image::Rgba;
 
+use super::{
+    color_utils::{AlphaHandling, ColorMatcher, ColorMetric, ColorUtils},
+    coordinate::Coordinate,
+    neighborhood::{BorderRule, Connectivity},
+};
+
+/// Serialized with the results of a scan, so exports record how they were made.
+#[derive(serde_derive::Serialize)]
 pub struct Config {
+    #[serde(serialize_with = "serialize_color")]
     pub searched_color: Rgba<u8>,
+    /// Marker color of cells that are ignored when a pattern is created from a masked image.
+    #[serde(serialize_with = "serialize_color")]
+    pub dont_care_color: Rgba<u8>,
     pub pattern_extracting_tolerance: u8,
-    pub pattern_searching_tolerance: u8,
+    pub pattern_searching_tolerance: f32,
+    /// Also search for rotated and mirrored variants of patterns, on by default.
+    pub search_variants: bool,
+    /// Integer scales patterns are searched at, 1 is the size of the pattern image.
+    pub pattern_scales: Vec<u32>,
+    /// Share of pattern pixels that has to be inside the image for an edge match.
+    pub edge_match_min_visible_ratio: f32,
+    /// Pattern pixels allowed to have a different color in fuzzy matches.
+    pub max_pattern_mismatches: u32,
+    /// Border pixels allowed to have the pattern color in fuzzy matches.
+    pub max_border_violations: u32,
+    /// Metric used to compare colors while searching, `pattern_searching_tolerance` is in its units.
+    pub color_metric: ColorMetric,
+    pub alpha_handling: AlphaHandling,
+    /// Pixels around a match that must not have the pattern color.
+    pub border_rule: BorderRule,
+    /// Which neighbors belong to the same region when labeling connected components.
+    pub component_connectivity: Connectivity,
+    /// Canvas coordinate of the top-left pixel of scanned images, used for match positions.
+    pub canvas_origin: Coordinate,
 }
 
 impl Config {
     pub fn new(
         searched_color: Rgba<u8>,
         pattern_extracting_tolerance: u8,
-        pattern_searching_tolerance: u8,
+        pattern_searching_tolerance: f32,
     ) -> Self {
         Config {
             searched_color,
+            dont_care_color: Rgba([255, 0, 255, 255]),
             pattern_extracting_tolerance,
             pattern_searching_tolerance,
+            search_variants: true,
+            pattern_scales: vec![1],
+            edge_match_min_visible_ratio: 0.5,
+            max_pattern_mismatches: 1,
+            max_border_violations: 1,
+            color_metric: ColorMetric::ChannelDelta,
+            alpha_handling: AlphaHandling::Ignore,
+            border_rule: BorderRule::new_default(),
+            component_connectivity: Connectivity::Eight,
+            canvas_origin: Coordinate::new(0, 0),
         }
     }
 
     pub fn new_default() -> Self {
         Config {
             searched_color: Rgba([0, 0, 0, 0]),
+            dont_care_color: Rgba([255, 0, 255, 255]),
             pattern_extracting_tolerance: 1,
-            pattern_searching_tolerance: 1,
+            pattern_searching_tolerance: 1.0,
+            search_variants: true,
+            pattern_scales: vec![1],
+            edge_match_min_visible_ratio: 0.5,
+            max_pattern_mismatches: 1,
+            max_border_violations: 1,
+            color_metric: ColorMetric::ChannelDelta,
+            alpha_handling: AlphaHandling::Ignore,
+            border_rule: BorderRule::new_default(),
+            component_connectivity: Connectivity::Eight,
+            canvas_origin: Coordinate::new(0, 0),
         }
     }
+
+    pub fn color_matcher(&self) -> ColorMatcher {
+        ColorMatcher::new(
+            self.color_metric,
+            self.pattern_searching_tolerance,
+            self.alpha_handling,
+        )
+    }
+}
+
+fn serialize_color<S>(color: &Rgba<u8>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&ColorUtils::to_hex(*color))
 }
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+
+use super::{
+    bounding_box::BoundingBox, color_utils::ColorMatcher, coordinate::Coordinate,
+    neighborhood::Connectivity, pattern::Pattern, pattern_variant::PatternVariant,
+};
+
+#[derive(Debug, Clone)]
+pub struct ConnectedComponent {
+    /// Color of the first pixel of the component in reading order.
+    pub color: Rgba<u8>,
+    pub pixel_count: u32,
+    pub bounding_box: BoundingBox,
+    /// Shape of the component with its bounding box moved to (0, 0).
+    pub shape: Pattern,
+}
+
+/// Every pixel of an image labeled with the index of the same-colored region it belongs to.
+pub struct ComponentLabeling {
+    width: u32,
+    height: u32,
+    labels: Vec<usize>,
+    components: Vec<ConnectedComponent>,
+}
+
+impl ComponentLabeling {
+    /// Components are numbered in the reading order of their first pixel. Pixels join
+    /// a component when `color_matcher` matches them with the color of its first pixel.
+    pub fn from_image(
+        image: &DynamicImage,
+        connectivity: Connectivity,
+        color_matcher: &ColorMatcher,
+    ) -> Self {
+        let pixels = image.to_rgba8();
+        let (width, height) = pixels.dimensions();
+        let neighbor_offsets = connectivity.offsets(1);
+
+        let mut labels = vec![UNLABELED; (width * height) as usize];
+        let mut components = Vec::new();
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = (y * width + x) as usize;
+
+                if labels[index] != UNLABELED {
+                    continue;
+                }
+
+                let label = components.len();
+                let color = *pixels.get_pixel(x, y);
+                let coordinates = flood_fill(
+                    &pixels,
+                    Coordinate::new(x as i32, y as i32),
+                    &neighbor_offsets,
+                    color_matcher,
+                    &mut labels,
+                    label,
+                );
+                let bounding_box =
+                    BoundingBox::from_coordinates(&coordinates).expect("seed is always filled");
+
+                components.push(ConnectedComponent {
+                    color,
+                    pixel_count: coordinates.len() as u32,
+                    bounding_box,
+                    shape: Pattern::new_from_coordinates(coordinates)
+                        .transformed(PatternVariant::identity()),
+                });
+            }
+        }
+
+        ComponentLabeling {
+            width,
+            height,
+            labels,
+            components,
+        }
+    }
+
+    pub fn get_components(&self) -> &Vec<ConnectedComponent> {
+        &self.components
+    }
+
+    pub fn get_label(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(self.labels[(y * self.width + x) as usize])
+    }
+
+    pub fn get_component_at(&self, x: u32, y: u32) -> Option<&ConnectedComponent> {
+        self.get_label(x, y).map(|label| &self.components[label])
+    }
+
+    pub fn components_of_size(&self, pixel_count: u32) -> Vec<&ConnectedComponent> {
+        self.components
+            .iter()
+            .filter(|component| component.pixel_count == pixel_count)
+            .collect()
+    }
+
+    /// Number of components for every component size.
+    pub fn size_histogram(&self) -> BTreeMap<u32, usize> {
+        let mut histogram = BTreeMap::new();
+
+        for component in &self.components {
+            *histogram.entry(component.pixel_count).or_insert(0) += 1;
+        }
+
+        histogram
+    }
+}
+
+/// Labels every unlabeled pixel reachable from `seed` through pixels matching the seed color
+/// with `label` and returns their coordinates, `labels` has one entry per pixel.
+pub fn flood_fill(
+    pixels: &RgbaImage,
+    seed: Coordinate,
+    neighbor_offsets: &[(i32, i32)],
+    color_matcher: &ColorMatcher,
+    labels: &mut [usize],
+    label: usize,
+) -> Vec<Coordinate> {
+    let (width, height) = pixels.dimensions();
+    let color = *pixels.get_pixel(seed.x as u32, seed.y as u32);
+
+    let mut coordinates = Vec::new();
+    let mut stack = vec![seed];
+    labels[(seed.y as u32 * width + seed.x as u32) as usize] = label;
+
+    while let Some(coordinate) = stack.pop() {
+        coordinates.push(coordinate);
+
+        for (offset_x, offset_y) in neighbor_offsets {
+            let neighbor = Coordinate::new(coordinate.x + offset_x, coordinate.y + offset_y);
+
+            if neighbor.x < 0
+                || neighbor.y < 0
+                || neighbor.x >= width as i32
+                || neighbor.y >= height as i32
+            {
+                continue;
+            }
+
+            let neighbor_index = (neighbor.y as u32 * width + neighbor.x as u32) as usize;
+
+            if labels[neighbor_index] == UNLABELED
+                && color_matcher.matches(
+                    color,
+                    *pixels.get_pixel(neighbor.x as u32, neighbor.y as u32),
+                )
+            {
+                labels[neighbor_index] = label;
+                stack.push(neighbor);
+            }
+        }
+    }
+
+    coordinates
+}
+
+/// Label of pixels that are not part of any component yet.
+pub const UNLABELED: usize = usize::MAX;
+
+#[cfg(test)]
+mod tests {
+    use super::ComponentLabeling;
+    use crate::image_scanner::bounding_box::BoundingBox;
+    use crate::image_scanner::coordinate::Coordinate;
+    use crate::image_scanner::neighborhood::Connectivity;
+    use crate::test_utils::{load_image, COLOR_MATCHER};
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    fn diagonal_pixels() -> DynamicImage {
+        let mut image = RgbaImage::from_pixel(4, 3, WHITE);
+        image.put_pixel(1, 0, BLACK);
+        image.put_pixel(2, 1, BLACK);
+        image.put_pixel(2, 2, BLACK);
+
+        DynamicImage::ImageRgba8(image)
+    }
+
+    #[test]
+    fn test_eight_connected_components() {
+        let labeling =
+            ComponentLabeling::from_image(&diagonal_pixels(), Connectivity::Eight, &COLOR_MATCHER);
+        let components = labeling.get_components();
+
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[1].color, BLACK);
+        assert_eq!(components[1].pixel_count, 3);
+        assert_eq!(components[1].bounding_box, BoundingBox::new(1, 0, 2, 2));
+        assert_eq!(
+            components[1].shape.get_coordinates(),
+            &vec![
+                Coordinate { x: 0, y: 0 },
+                Coordinate { x: 1, y: 1 },
+                Coordinate { x: 1, y: 2 },
+            ]
+        );
+        assert_eq!(labeling.get_label(2, 2), Some(1));
+        assert_eq!(labeling.get_label(4, 0), None);
+    }
+
+    #[test]
+    fn test_four_connected_components() {
+        let labeling =
+            ComponentLabeling::from_image(&diagonal_pixels(), Connectivity::Four, &COLOR_MATCHER);
+
+        // Without diagonal steps the black pixels also split the white background.
+        assert_eq!(labeling.get_components().len(), 4);
+        assert_eq!(labeling.components_of_size(2).len(), 1);
+        assert_eq!(labeling.get_component_at(1, 0).unwrap().pixel_count, 1);
+    }
+
+    #[test]
+    fn test_size_histogram_of_crewmates() {
+        let image = load_image("assets/images/8_crewmates.png");
+
+        // The asset has slight color noise.
+        let labeling = ComponentLabeling::from_image(&image, Connectivity::Eight, &COLOR_MATCHER);
+
+        assert_eq!(labeling.size_histogram().get(&11), Some(&8));
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+
+use image::{DynamicImage, GenericImageView, Rgba};
+use rayon::prelude::*;
+
+use super::{
+    color_utils::ColorMatcher, coordinate::Coordinate, neighborhood::BorderRule, pattern::Pattern,
+    scan_image::check_pattern_fits, ScanError,
+};
+
+#[derive(Debug, Clone)]
+pub struct FuzzyMatch {
+    /// Every pixel of the matched pattern in image coordinates, deviating ones included.
+    pub pattern: Pattern,
+    /// Share of pattern and border pixels that are as expected, 1.0 for intact art.
+    pub score: f32,
+    /// Pattern pixels of a different color and border pixels of the pattern color.
+    pub deviating_coordinates: Vec<Coordinate>,
+}
+
+/// Like `scan_image`, but tolerates up to `max_pattern_mismatches` pattern pixels of
+/// a different color and `max_border_violations` border pixels of the pattern color.
+/// Matches may overlap, see `suppress_overlapping_matches`.
+pub fn scan_image_fuzzy(
+    image: &DynamicImage,
+    search_pattern: &Pattern,
+    color_matcher: &ColorMatcher,
+    border_rule: &BorderRule,
+    max_pattern_mismatches: u32,
+    max_border_violations: u32,
+) -> Result<Vec<FuzzyMatch>, ScanError> {
+    check_pattern_fits(image, search_pattern)?;
+    let search_pattern = &search_pattern.normalized();
+
+    let (img_width, img_height) = image.dimensions();
+    let (window_width, window_height) = search_pattern.get_window_size();
+
+    let adjacent_pixel_coordinates =
+        search_pattern.generate_coordinates_of_adjacent_pixels(border_rule);
+    let adjacent_pixel_coordinates = &adjacent_pixel_coordinates;
+
+    let matches = (0..=(img_height - window_height))
+        .into_par_iter()
+        .flat_map_iter(|offset_y| {
+            // Reused by every window of the row.
+            let mut pattern_pixels = Vec::new();
+            let mut color_counts = Vec::new();
+
+            (0..=(img_width - window_width)).filter_map(move |offset_x| {
+                fuzzy_pattern_in_window(
+                    image,
+                    offset_x as i32,
+                    offset_y as i32,
+                    search_pattern,
+                    color_matcher,
+                    adjacent_pixel_coordinates,
+                    max_pattern_mismatches,
+                    max_border_violations,
+                    &mut pattern_pixels,
+                    &mut color_counts,
+                )
+            })
+        })
+        .collect();
+
+    Ok(matches)
+}
+
+/// Keeps the best scoring match of every group of overlapping matches.
+pub fn suppress_overlapping_matches(mut matches: Vec<FuzzyMatch>) -> Vec<FuzzyMatch> {
+    matches.sort_by(|first, second| {
+        second.score.total_cmp(&first.score).then_with(|| {
+            first
+                .pattern
+                .get_coordinates()
+                .first()
+                .cmp(&second.pattern.get_coordinates().first())
+        })
+    });
+
+    let mut claimed_pixels: HashSet<Coordinate> = HashSet::new();
+    let mut kept_matches = Vec::new();
+
+    for fuzzy_match in matches {
+        let coordinates = fuzzy_match.pattern.get_coordinates();
+
+        if coordinates.iter().any(|c| claimed_pixels.contains(c)) {
+            continue;
+        }
+
+        claimed_pixels.extend(coordinates.iter().copied());
+        kept_matches.push(fuzzy_match);
+    }
+
+    kept_matches
+}
+
+#[allow(clippy::too_many_arguments)]
+fn fuzzy_pattern_in_window(
+    image: &DynamicImage,
+    offset_x: i32,
+    offset_y: i32,
+    search_pattern: &Pattern,
+    color_matcher: &ColorMatcher,
+    adjacent_pixel_coordinates: &[Coordinate],
+    max_pattern_mismatches: u32,
+    max_border_violations: u32,
+    pattern_pixels: &mut Vec<(Coordinate, Rgba<u8>)>,
+    color_counts: &mut Vec<(Rgba<u8>, usize)>,
+) -> Option<FuzzyMatch> {
+    pattern_pixels.clear();
+    pattern_pixels.extend(search_pattern.get_coordinates().iter().map(|c| {
+        let coordinate = Coordinate::new(c.x + offset_x, c.y + offset_y);
+        let color = image.get_pixel(coordinate.x as u32, coordinate.y as u32);
+        (coordinate, color)
+    }));
+
+    let reference_color = most_common_color(pattern_pixels, color_matcher, color_counts)?;
+
+    let mut deviating_coordinates = Vec::new();
+
+    for (coordinate, color) in pattern_pixels.iter() {
+        if !color_matcher.matches(reference_color, *color) {
+            deviating_coordinates.push(*coordinate);
+
+            if deviating_coordinates.len() as u32 > max_pattern_mismatches {
+                return None;
+            }
+        }
+    }
+
+    let pattern_mismatches = deviating_coordinates.len();
+    let (img_width, img_height) = (image.width() as i32, image.height() as i32);
+    let mut checked_border_pixels = 0;
+
+    for c in adjacent_pixel_coordinates {
+        let coordinate = Coordinate::new(c.x + offset_x, c.y + offset_y);
+
+        if coordinate.x < 0
+            || coordinate.y < 0
+            || coordinate.x >= img_width
+            || coordinate.y >= img_height
+        {
+            continue;
+        }
+
+        checked_border_pixels += 1;
+        let color = image.get_pixel(coordinate.x as u32, coordinate.y as u32);
+
+        if color_matcher.matches(reference_color, color) {
+            deviating_coordinates.push(coordinate);
+
+            if (deviating_coordinates.len() - pattern_mismatches) as u32 > max_border_violations {
+                return None;
+            }
+        }
+    }
+
+    let checked_pixels = pattern_pixels.len() + checked_border_pixels;
+    let score = 1.0 - deviating_coordinates.len() as f32 / checked_pixels as f32;
+
+    Some(FuzzyMatch {
+        pattern: Pattern::new_with_variant(
+            pattern_pixels.iter().map(|(c, _)| *c).collect(),
+            search_pattern.get_variant(),
+        ),
+        score,
+        deviating_coordinates,
+    })
+}
+
+/// Color that the most pixels match. Pixels are first counted per exact color, so the
+/// matcher only compares the few distinct colors of a window with each other.
+fn most_common_color(
+    pixels: &[(Coordinate, Rgba<u8>)],
+    color_matcher: &ColorMatcher,
+    color_counts: &mut Vec<(Rgba<u8>, usize)>,
+) -> Option<Rgba<u8>> {
+    color_counts.clear();
+
+    for (_, color) in pixels {
+        match color_counts.iter_mut().find(|(other, _)| other == color) {
+            Some((_, count)) => *count += 1,
+            None => color_counts.push((*color, 1)),
+        }
+    }
+
+    // Reversed, so ties are won by the color seen first.
+    color_counts
+        .iter()
+        .rev()
+        .map(|(color, _)| {
+            let equal_pixels: usize = color_counts
+                .iter()
+                .filter(|(other, _)| color_matcher.matches(*color, *other))
+                .map(|(_, count)| count)
+                .sum();
+            (equal_pixels, *color)
+        })
+        .max_by_key(|(equal_pixels, _)| *equal_pixels)
+        .map(|(_, color)| color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{most_common_color, scan_image_fuzzy, suppress_overlapping_matches};
+    use crate::image_scanner::{coordinate::Coordinate, pattern::Pattern};
+    use crate::test_utils::{load_image, BORDER_RULE, COLOR_MATCHER};
+    use image::{DynamicImage, Rgba};
+
+    fn crewmate() -> Pattern {
+        Pattern::from_image(
+            load_image("assets/images/crewmate.png"),
+            Rgba([0, 0, 0, 0]),
+            1,
+        )
+    }
+
+    fn damaged_crewmate_image() -> DynamicImage {
+        let mut image = load_image("assets/images/crewmate_with_borders.png").into_rgba8();
+        // Grief one pixel of the body and paint one border pixel in the body color.
+        image.put_pixel(2, 3, Rgba([255, 255, 255, 255]));
+        image.put_pixel(5, 5, Rgba([0, 0, 0, 255]));
+        DynamicImage::ImageRgba8(image)
+    }
+
+    #[test]
+    fn test_scan_image_fuzzy_intact() {
+        let image = load_image("assets/images/crewmate_with_borders.png");
+
+        let matches =
+            scan_image_fuzzy(&image, &crewmate(), &COLOR_MATCHER, &BORDER_RULE, 0, 0).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].score, 1.0);
+        assert!(matches[0].deviating_coordinates.is_empty());
+    }
+
+    #[test]
+    fn test_scan_image_fuzzy_damaged() {
+        let image = damaged_crewmate_image();
+
+        let strict_matches =
+            scan_image_fuzzy(&image, &crewmate(), &COLOR_MATCHER, &BORDER_RULE, 0, 0).unwrap();
+        let fuzzy_matches = suppress_overlapping_matches(
+            scan_image_fuzzy(&image, &crewmate(), &COLOR_MATCHER, &BORDER_RULE, 1, 1).unwrap(),
+        );
+
+        assert!(strict_matches.is_empty());
+        assert_eq!(fuzzy_matches.len(), 1);
+        assert!(fuzzy_matches[0].score < 1.0);
+
+        let mut deviating_coordinates = fuzzy_matches[0].deviating_coordinates.clone();
+        deviating_coordinates.sort();
+        assert_eq!(
+            deviating_coordinates,
+            vec![Coordinate::new(2, 3), Coordinate::new(5, 5)]
+        );
+    }
+
+    #[test]
+    fn test_most_common_color_merges_matching_colors() {
+        let pixels: Vec<(Coordinate, Rgba<u8>)> = [
+            Rgba([0, 0, 0, 255]),
+            Rgba([1, 1, 1, 255]),
+            Rgba([255, 255, 255, 255]),
+            Rgba([255, 255, 255, 255]),
+            Rgba([0, 0, 0, 255]),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(x, color)| (Coordinate::new(x as i32, 0), color))
+        .collect();
+
+        let color = most_common_color(&pixels, &COLOR_MATCHER, &mut Vec::new()).unwrap();
+
+        assert_eq!(color, Rgba([0, 0, 0, 255]));
+    }
+}
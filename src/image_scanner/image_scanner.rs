@@ -1,40 +1,592 @@
-use image::DynamicImage;
+use std::{collections::HashMap, error::Error, ops::RangeInclusive};
 
-use super::{pattern::Pattern, scan_image::scan_image, Config};
+use image::{DynamicImage, GenericImageView};
+use rayon::prelude::*;
+
+use crate::image_io::ImageIO;
+
+use super::{
+    color_utils::ColorMatcher,
+    connected_components::ComponentLabeling,
+    fuzzy_match::{scan_image_fuzzy, suppress_overlapping_matches, FuzzyMatch},
+    multi_color_pattern::{MultiColorPattern, RoleColor, RoleMapping},
+    pattern::Pattern,
+    pattern_library::PatternLibrary,
+    scan_image::{
+        check_pattern_fits, scan_image, scan_image_edges, scan_image_for_multiple_patterns,
+    },
+    scan_match::Match,
+    scan_multi_color::scan_image_multi_color,
+    shape_frequency::{count_shape_frequencies, ShapeFrequency},
+    Config, ScanError,
+};
+
+pub type LibraryScanResult = Result<HashMap<String, Vec<Match>>, ScanError>;
 
 pub struct ImageScanner {
     config: Config,
 }
 
 impl ImageScanner {
-    pub fn new(config: Config) -> Self {
-        ImageScanner { config }
+    /// Fails when the config has no pattern scales or a scale of 0.
+    pub fn new(config: Config) -> Result<Self, ScanError> {
+        if config.pattern_scales.is_empty() || config.pattern_scales.contains(&0) {
+            return Err(ScanError::InvalidPatternScales(config.pattern_scales));
+        }
+
+        Ok(ImageScanner { config })
     }
 
+    pub fn get_config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Pixels are classified with the metric and alpha handling of the search and the
+    /// extracting tolerance, like `extract_pattern_at`.
     pub fn create_pattern(&self, image: DynamicImage) -> Pattern {
-        Pattern::from_image(
+        Pattern::from_image_with_matcher(
+            image,
+            self.config.searched_color,
+            &self.extracting_color_matcher(),
+        )
+    }
+
+    /// Pixels of the searched color must match, pixels of the don't-care color are ignored
+    /// and all other pixels must not match.
+    pub fn create_masked_pattern(&self, image: DynamicImage) -> Pattern {
+        Pattern::from_masked_image_with_matcher(
             image,
             self.config.searched_color,
+            self.config.dont_care_color,
+            &self.extracting_color_matcher(),
+        )
+    }
+
+    pub fn create_multi_color_pattern(
+        &self,
+        image: DynamicImage,
+        role_mappings: &[RoleMapping],
+    ) -> MultiColorPattern {
+        MultiColorPattern::from_role_image(
+            image,
+            role_mappings,
             self.config.pattern_extracting_tolerance,
         )
     }
 
+    /// Variants and scales that do not fit into the image are skipped,
+    /// an error is returned only when the pattern itself does not fit.
     pub fn scan_image_for_patterns(
         &self,
+        pattern_name: &str,
+        search_pattern: &Pattern,
+        image: &DynamicImage,
+    ) -> Result<Vec<Match>, ScanError> {
+        check_pattern_fits(image, search_pattern)?;
+
+        let mut matches = Vec::new();
+
+        for pattern in self.search_patterns(search_pattern) {
+            if check_pattern_fits(image, &pattern).is_ok() {
+                matches.extend(
+                    scan_image(
+                        image,
+                        &pattern,
+                        &self.config.color_matcher(),
+                        &self.config.border_rule,
+                    )?
+                    .iter()
+                    .map(|found_pattern| self.to_match(pattern_name, found_pattern, image)),
+                );
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Finds intact as well as damaged art within the mismatch limits of the config.
+    /// The color of a match is read from its first pixel that is as expected.
+    /// Overlapping matches of all variants and scales are reduced to the best scoring one.
+    pub fn scan_image_for_fuzzy_patterns(
+        &self,
+        pattern_name: &str,
+        search_pattern: &Pattern,
+        image: &DynamicImage,
+    ) -> Result<Vec<Match>, ScanError> {
+        check_pattern_fits(image, search_pattern)?;
+
+        let mut matches = Vec::new();
+
+        for pattern in self.search_patterns(search_pattern) {
+            if check_pattern_fits(image, &pattern).is_ok() {
+                matches.extend(scan_image_fuzzy(
+                    image,
+                    &pattern,
+                    &self.config.color_matcher(),
+                    &self.config.border_rule,
+                    self.config.max_pattern_mismatches,
+                    self.config.max_border_violations,
+                )?);
+            }
+        }
+
+        Ok(suppress_overlapping_matches(matches)
+            .iter()
+            .map(|fuzzy_match| self.fuzzy_to_match(pattern_name, fuzzy_match, image))
+            .collect())
+    }
+
+    /// Like `scan_image_for_patterns`, found patterns have their roles fixed to the matched colors.
+    pub fn scan_image_for_multi_color_patterns(
+        &self,
+        pattern_name: &str,
+        search_pattern: &MultiColorPattern,
+        image: &DynamicImage,
+    ) -> Result<Vec<Match>, ScanError> {
+        check_pattern_fits(image, &search_pattern.to_pattern())?;
+
+        let variants = if self.config.search_variants {
+            search_pattern.variants()
+        } else {
+            vec![search_pattern.clone()]
+        };
+
+        let mut found_patterns = Vec::new();
+
+        for variant in &variants {
+            for scale in &self.config.pattern_scales {
+                let pattern = variant.upscaled(*scale);
+
+                if check_pattern_fits(image, &pattern.to_pattern()).is_ok() {
+                    found_patterns.extend(
+                        scan_image_multi_color(
+                            image,
+                            &pattern,
+                            &self.config.color_matcher(),
+                            &self.config.border_rule,
+                        )?
+                        .iter()
+                        .map(|found_pattern| {
+                            self.multi_color_to_match(pattern_name, found_pattern)
+                        }),
+                    );
+                }
+            }
+        }
+
+        Ok(found_patterns)
+    }
+
+    /// Matches of the pattern that are partially clipped by the image border.
+    pub fn scan_image_for_edge_patterns(
+        &self,
+        pattern_name: &str,
         search_pattern: &Pattern,
         image: &DynamicImage,
-    ) -> Vec<Pattern> {
-        scan_image(
+    ) -> Vec<Match> {
+        self.search_patterns(search_pattern)
+            .iter()
+            .flat_map(|pattern| {
+                scan_image_edges(
+                    image,
+                    pattern,
+                    &self.config.color_matcher(),
+                    &self.config.border_rule,
+                    self.config.edge_match_min_visible_ratio,
+                )
+            })
+            .map(|found_pattern| self.to_match(pattern_name, &found_pattern, image))
+            .collect()
+    }
+
+    /// Pattern of the same-colored region around (`x`, `y`), colors are compared
+    /// with the extracting tolerance.
+    pub fn extract_pattern_at(&self, image: &DynamicImage, x: u32, y: u32) -> Option<Pattern> {
+        Pattern::from_flood_fill(
+            image,
+            x,
+            y,
+            self.config.component_connectivity,
+            &self.extracting_color_matcher(),
+        )
+    }
+
+    /// Labels every same-colored region of the image, colors are compared with the search tolerance.
+    pub fn label_connected_components(&self, image: &DynamicImage) -> ComponentLabeling {
+        ComponentLabeling::from_image(
             image,
-            search_pattern,
-            self.config.pattern_searching_tolerance,
+            self.config.component_connectivity,
+            &self.config.color_matcher(),
         )
     }
 
+    /// Most frequent shapes among the components with a pixel count in `pixel_counts`,
+    /// rotated and mirrored shapes are grouped together when variants are searched.
+    pub fn discover_shapes(
+        &self,
+        image: &DynamicImage,
+        pixel_counts: RangeInclusive<u32>,
+    ) -> Vec<ShapeFrequency> {
+        let labeling = self.label_connected_components(image);
+
+        count_shape_frequencies(
+            labeling.get_components(),
+            self.config.search_variants,
+            pixel_counts,
+        )
+    }
+
+    /// Searches for every pattern of the library in one pass, results are grouped by pattern name.
+    pub fn scan_image_for_library(
+        &self,
+        library: &PatternLibrary,
+        image: &DynamicImage,
+    ) -> HashMap<String, Vec<Match>> {
+        let mut entry_indices = Vec::new();
+        let mut search_patterns = Vec::new();
+
+        for (entry_index, entry) in library.get_entries().iter().enumerate() {
+            for search_pattern in self.search_patterns(&entry.pattern) {
+                entry_indices.push(entry_index);
+                search_patterns.push(search_pattern);
+            }
+        }
+
+        let matches = scan_image_for_multiple_patterns(
+            image,
+            &search_patterns,
+            &self.config.color_matcher(),
+            &self.config.border_rule,
+        )
+        .into_iter()
+        .map(|(pattern_index, found_pattern)| (entry_indices[pattern_index], found_pattern))
+        .collect();
+
+        library
+            .resolve_matches(matches)
+            .into_iter()
+            .map(|(name, found_patterns)| {
+                let matches = found_patterns
+                    .iter()
+                    .map(|found_pattern| self.to_match(&name, found_pattern, image))
+                    .collect();
+
+                (name, matches)
+            })
+            .collect()
+    }
+
+    fn extracting_color_matcher(&self) -> ColorMatcher {
+        ColorMatcher::new(
+            self.config.color_metric,
+            self.config.pattern_extracting_tolerance as f32,
+            self.config.alpha_handling,
+        )
+    }
+
+    /// All variants and scales of `pattern` enabled in the config.
+    fn search_patterns(&self, pattern: &Pattern) -> Vec<Pattern> {
+        let variants = if self.config.search_variants {
+            pattern.variants()
+        } else {
+            vec![pattern.clone()]
+        };
+
+        variants
+            .iter()
+            .flat_map(|variant| {
+                self.config
+                    .pattern_scales
+                    .iter()
+                    .map(move |scale| variant.upscaled(*scale))
+            })
+            .collect()
+    }
+
+    fn to_match(&self, pattern_name: &str, found_pattern: &Pattern, image: &DynamicImage) -> Match {
+        Match::from_found_pattern(
+            pattern_name,
+            found_pattern,
+            image,
+            self.config.canvas_origin,
+        )
+    }
+
+    fn fuzzy_to_match(
+        &self,
+        pattern_name: &str,
+        fuzzy_match: &FuzzyMatch,
+        image: &DynamicImage,
+    ) -> Match {
+        let coordinates = fuzzy_match.pattern.get_coordinates();
+        let color_coordinate = coordinates
+            .iter()
+            .find(|coordinate| !fuzzy_match.deviating_coordinates.contains(coordinate))
+            .unwrap_or(&coordinates[0]);
+
+        let mut found_match = Match::new(
+            pattern_name,
+            &fuzzy_match.pattern,
+            vec![image.get_pixel(color_coordinate.x as u32, color_coordinate.y as u32)],
+            fuzzy_match.score,
+            self.config.canvas_origin,
+        );
+        found_match.image_deviating_coordinates = fuzzy_match.deviating_coordinates.clone();
+
+        found_match
+    }
+
+    /// Colors of the roles in their order, roles of found patterns are always fixed.
+    fn multi_color_to_match(&self, pattern_name: &str, found_pattern: &MultiColorPattern) -> Match {
+        let colors = found_pattern
+            .get_roles()
+            .iter()
+            .filter_map(|role| match role.color {
+                RoleColor::Fixed(color) => Some(color),
+                RoleColor::Free => None,
+            })
+            .collect();
+
+        Match::new(
+            pattern_name,
+            &found_pattern.to_pattern(),
+            colors,
+            1.0,
+            self.config.canvas_origin,
+        )
+    }
+
+    /// Loads and scans the images in parallel, results are keyed by image path and
+    /// images that failed to load get their own error.
     pub fn scan_multiple_images_for_patterns(
         &self,
-        images: &Vec<DynamicImage>,
-    ) -> Vec<Vec<Pattern>> {
-        todo!()
+        paths: &[&str],
+        library: &PatternLibrary,
+    ) -> HashMap<String, LibraryScanResult> {
+        paths
+            .par_iter()
+            .map(|path| {
+                let result = match ImageIO::load_image(path) {
+                    Ok(image) => Ok(self.scan_image_for_library(library, &image)),
+                    Err(error) => Err(ScanError::ImageLoad {
+                        path: path.to_string(),
+                        message: error.to_string(),
+                    }),
+                };
+
+                (path.to_string(), result)
+            })
+            .collect()
+    }
+
+    pub fn scan_directory_for_patterns(
+        &self,
+        directory: &str,
+        library: &PatternLibrary,
+    ) -> Result<HashMap<String, LibraryScanResult>, Box<dyn Error>> {
+        let paths = ImageIO::list_images_in_directory(directory)?;
+        let paths: Vec<&str> = paths.iter().map(|path| path.as_str()).collect();
+
+        Ok(self.scan_multiple_images_for_patterns(&paths, library))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImageScanner;
+    use crate::image_io::ImageIO;
+    use crate::image_scanner::{
+        BoundingBox, Config, Coordinate, PatternLibrary, PatternVariant, ScanError,
+    };
+    use crate::test_utils::TempDirectory;
+    use image::{DynamicImage, Rgba};
+    use std::fs;
+
+    fn crewmate_library(scanner: &ImageScanner) -> PatternLibrary {
+        let crewmate = ImageIO::load_image("assets/images/crewmate.png").unwrap();
+
+        let mut library = PatternLibrary::new();
+        library
+            .add_pattern("crewmate", scanner.create_pattern(crewmate), 0)
+            .unwrap();
+        library
+    }
+
+    #[test]
+    fn test_scan_multiple_images_for_patterns() {
+        let scanner = ImageScanner::new(Config::new_default()).unwrap();
+        let library = crewmate_library(&scanner);
+
+        let results = scanner.scan_multiple_images_for_patterns(
+            &[
+                "assets/images/8_crewmates.png",
+                "assets/images/4_crewmates_adjacent_test.png",
+                "assets/images/missing.png",
+            ],
+            &library,
+        );
+
+        assert_eq!(
+            results["assets/images/8_crewmates.png"].as_ref().unwrap()["crewmate"].len(),
+            8
+        );
+        assert_eq!(
+            results["assets/images/4_crewmates_adjacent_test.png"]
+                .as_ref()
+                .unwrap()["crewmate"]
+                .len(),
+            4
+        );
+        assert!(matches!(
+            results["assets/images/missing.png"],
+            Err(ScanError::ImageLoad { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scan_directory_for_patterns() {
+        let directory = TempDirectory::new("scan_directory");
+        fs::copy(
+            "assets/images/8_crewmates.png",
+            directory.join("8_crewmates.png"),
+        )
+        .unwrap();
+        fs::write(directory.join("broken.png"), b"not an image").unwrap();
+
+        let scanner = ImageScanner::new(Config::new_default()).unwrap();
+        let library = crewmate_library(&scanner);
+
+        let results = scanner
+            .scan_directory_for_patterns(directory.get_path().to_str().unwrap(), &library)
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results.iter().filter(|(_, result)| result.is_err()).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_scan_image_for_patterns_returns_matches() {
+        let mut config = Config::new_default();
+        config.canvas_origin = Coordinate::new(-1500, -1000);
+        let scanner = ImageScanner::new(config).unwrap();
+        let image = ImageIO::load_image("assets/images/crewmate_with_borders.png").unwrap();
+        let crewmate =
+            scanner.create_pattern(ImageIO::load_image("assets/images/crewmate.png").unwrap());
+
+        let matches = scanner
+            .scan_image_for_patterns("crewmate", &crewmate, &image)
+            .unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pattern_name, "crewmate");
+        assert_eq!(matches[0].variant, PatternVariant::identity());
+        assert_eq!(
+            matches[0].bounding_box(),
+            BoundingBox::new(-1499, -999, -1496, -996)
+        );
+        assert_eq!(matches[0].image_bounding_box, BoundingBox::new(1, 1, 4, 4));
+        assert_eq!(matches[0].pixel_count(), 11);
+        assert_eq!(matches[0].score, 1.0);
+    }
+
+    #[test]
+    fn test_scan_image_for_patterns_finds_variants_unless_disabled() {
+        let image = ImageIO::load_image("assets/images/crewmate_with_borders.png")
+            .unwrap()
+            .fliph();
+        let crewmate_image = ImageIO::load_image("assets/images/crewmate.png").unwrap();
+
+        let scanner = ImageScanner::new(Config::new_default()).unwrap();
+        let crewmate = scanner.create_pattern(crewmate_image.clone());
+        let matches = scanner
+            .scan_image_for_patterns("crewmate", &crewmate, &image)
+            .unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].variant.mirrored);
+
+        let mut config = Config::new_default();
+        config.search_variants = false;
+        let scanner = ImageScanner::new(config).unwrap();
+        let crewmate = scanner.create_pattern(crewmate_image);
+        assert!(scanner
+            .scan_image_for_patterns("crewmate", &crewmate, &image)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_pattern_scales() {
+        let mut config = Config::new_default();
+        config.pattern_scales = vec![];
+        assert_eq!(
+            ImageScanner::new(config).err(),
+            Some(ScanError::InvalidPatternScales(vec![]))
+        );
+
+        let mut config = Config::new_default();
+        config.pattern_scales = vec![1, 0];
+        assert_eq!(
+            ImageScanner::new(config).err(),
+            Some(ScanError::InvalidPatternScales(vec![1, 0]))
+        );
+    }
+
+    #[test]
+    fn test_scan_image_for_fuzzy_patterns_keeps_deviating_coordinates() {
+        let scanner = ImageScanner::new(Config::new_default()).unwrap();
+        let crewmate =
+            scanner.create_pattern(ImageIO::load_image("assets/images/crewmate.png").unwrap());
+        let mut image = ImageIO::load_image("assets/images/crewmate_with_borders.png")
+            .unwrap()
+            .into_rgba8();
+        let damaged_pixel = scanner
+            .scan_image_for_patterns(
+                "crewmate",
+                &crewmate,
+                &DynamicImage::ImageRgba8(image.clone()),
+            )
+            .unwrap()[0]
+            .image_coordinates[1];
+        image.put_pixel(
+            damaged_pixel.x as u32,
+            damaged_pixel.y as u32,
+            Rgba([255, 0, 0, 255]),
+        );
+
+        let matches = scanner
+            .scan_image_for_fuzzy_patterns("crewmate", &crewmate, &DynamicImage::ImageRgba8(image))
+            .unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].image_deviating_coordinates, vec![damaged_pixel]);
+        assert!(matches[0].score < 1.0);
+    }
+
+    #[test]
+    fn test_extract_pattern_at() {
+        let scanner = ImageScanner::new(Config::new_default()).unwrap();
+        let image = ImageIO::load_image("assets/images/8_crewmates.png").unwrap();
+        let crewmate =
+            scanner.create_pattern(ImageIO::load_image("assets/images/crewmate.png").unwrap());
+        let first_crewmate = scanner
+            .scan_image_for_patterns("crewmate", &crewmate, &image)
+            .unwrap()[0]
+            .image_coordinates[0];
+
+        let extracted = scanner
+            .extract_pattern_at(&image, first_crewmate.x as u32, first_crewmate.y as u32)
+            .unwrap();
+
+        assert_eq!(
+            scanner
+                .scan_image_for_patterns("extracted", &extracted, &image)
+                .unwrap()
+                .len(),
+            8
+        );
     }
 }
@@ -9,12 +9,13 @@ impl ImageUtils {
         offset_y: u32,
         window_width: u32,
         window_height: u32,
+        border_width: u32,
     ) -> SubImage<&DynamicImage> {
-        let border_x_start = offset_x.saturating_sub(1);
-        let border_y_start = offset_y.saturating_sub(1);
+        let border_x_start = offset_x.saturating_sub(border_width);
+        let border_y_start = offset_y.saturating_sub(border_width);
 
-        let border_x_end = (border_x_start + window_width + 2).min(image.width());
-        let border_y_end = (border_y_start + window_height + 2).min(image.height());
+        let border_x_end = (offset_x + window_width + border_width).min(image.width());
+        let border_y_end = (offset_y + window_height + border_width).min(image.height());
 
         image.view(
             border_x_start,
@@ -35,7 +36,7 @@ mod tests {
         let image: DynamicImage =
             DynamicImage::ImageRgba8(ImageBuffer::from_pixel(10, 10, Rgba([0, 10, 20, 255])));
 
-        let result = ImageUtils::create_view_with_border(&image, 2, 2, 5, 5);
+        let result = ImageUtils::create_view_with_border(&image, 2, 2, 5, 5, 1);
 
         assert_eq!(result.width(), 7);
         assert_eq!(result.height(), 7);
@@ -46,7 +47,7 @@ mod tests {
         let image: DynamicImage =
             DynamicImage::ImageRgba8(ImageBuffer::from_pixel(10, 10, Rgba([0, 10, 20, 255])));
 
-        let result = ImageUtils::create_view_with_border(&image, 8, 8, 5, 5);
+        let result = ImageUtils::create_view_with_border(&image, 8, 8, 5, 5, 1);
 
         assert_eq!(result.width(), 3);
         assert_eq!(result.height(), 3);
@@ -57,9 +58,20 @@ mod tests {
         let image: DynamicImage =
             DynamicImage::ImageRgba8(ImageBuffer::from_pixel(10, 10, Rgba([0, 10, 20, 255])));
 
-        let result = ImageUtils::create_view_with_border(&image, 2, 2, 5, 5);
+        let result = ImageUtils::create_view_with_border(&image, 2, 2, 5, 5, 1);
 
         assert_eq!(result.width(), 7);
         assert_eq!(result.height(), 7);
     }
+
+    #[test]
+    fn test_create_view_with_thick_border() {
+        let image: DynamicImage =
+            DynamicImage::ImageRgba8(ImageBuffer::from_pixel(10, 10, Rgba([0, 10, 20, 255])));
+
+        let result = ImageUtils::create_view_with_border(&image, 1, 3, 4, 2, 2);
+
+        assert_eq!(result.width(), 7);
+        assert_eq!(result.height(), 6);
+    }
 }
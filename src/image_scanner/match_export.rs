@@ -0,0 +1,251 @@
+use std::{error::Error, fs, path::Path};
+
+use image::Rgba;
+use serde_json::json;
+
+use super::{
+    color_utils::ColorUtils, config::Config, pattern_variant::Rotation, scan_match::Match,
+};
+
+#[derive(serde_derive::Serialize)]
+struct ExportHeader<'a> {
+    config: &'a Config,
+}
+
+#[derive(serde_derive::Serialize)]
+struct CsvRow<'a> {
+    pattern_name: &'a str,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    rotation: Rotation,
+    mirrored: bool,
+    scale: u32,
+    /// Hex colors separated by spaces.
+    colors: String,
+    score: f32,
+    pixel_count: usize,
+}
+
+/// Writes scan results for other tools, every saved export records the config of the scan.
+pub struct MatchExporter {}
+
+impl MatchExporter {
+    /// The first line holds the config, every following line is one match.
+    pub fn to_json_lines(matches: &[Match], config: &Config) -> Result<String, Box<dyn Error>> {
+        let mut lines = vec![serde_json::to_string(&ExportHeader { config })?];
+
+        for found_match in matches {
+            lines.push(serde_json::to_string(found_match)?);
+        }
+
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// One row per match with its position in canvas coordinates, the config is left out
+    /// so any CSV reader can parse the result.
+    pub fn to_csv(matches: &[Match]) -> Result<String, Box<dyn Error>> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        for found_match in matches {
+            let bounding_box = found_match.bounding_box();
+
+            writer.serialize(CsvRow {
+                pattern_name: &found_match.pattern_name,
+                x: bounding_box.min_x,
+                y: bounding_box.min_y,
+                width: bounding_box.width(),
+                height: bounding_box.height(),
+                rotation: found_match.variant.rotation,
+                mirrored: found_match.variant.mirrored,
+                scale: found_match.variant.scale,
+                colors: found_match
+                    .colors
+                    .iter()
+                    .map(|color| ColorUtils::to_hex(Rgba(*color)))
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                score: found_match.score,
+                pixel_count: found_match.pixel_count(),
+            })?;
+        }
+
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    /// FeatureCollection with the bounding box of every match as a polygon in canvas
+    /// coordinates, the polygon covers the whole area of its edge pixels.
+    pub fn to_geojson(matches: &[Match], config: &Config) -> Result<String, Box<dyn Error>> {
+        let features: Vec<serde_json::Value> = matches
+            .iter()
+            .map(|found_match| {
+                let bounding_box = found_match.bounding_box();
+                let (min_x, min_y) = (bounding_box.min_x, bounding_box.min_y);
+                let (max_x, max_y) = (bounding_box.max_x + 1, bounding_box.max_y + 1);
+
+                json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[
+                            [min_x, min_y],
+                            [max_x, min_y],
+                            [max_x, max_y],
+                            [min_x, max_y],
+                            [min_x, min_y],
+                        ]],
+                    },
+                    "properties": {
+                        "pattern_name": found_match.pattern_name,
+                        "variant": found_match.variant,
+                        "colors": found_match.colors,
+                        "score": found_match.score,
+                        "pixel_count": found_match.pixel_count(),
+                    },
+                })
+            })
+            .collect();
+
+        Ok(serde_json::to_string_pretty(&json!({
+            "type": "FeatureCollection",
+            "config": config,
+            "features": features,
+        }))?)
+    }
+
+    pub fn save_json_lines(
+        matches: &[Match],
+        config: &Config,
+        path: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        fs::write(
+            Path::new(path),
+            MatchExporter::to_json_lines(matches, config)?,
+        )?;
+        Ok(())
+    }
+
+    /// The config is written next to the CSV file, `matches.csv` gets `matches.config.json`.
+    pub fn save_csv(matches: &[Match], config: &Config, path: &str) -> Result<(), Box<dyn Error>> {
+        let path = Path::new(path);
+
+        fs::write(path, MatchExporter::to_csv(matches)?)?;
+        fs::write(
+            path.with_extension("config.json"),
+            serde_json::to_string_pretty(&ExportHeader { config })?,
+        )?;
+        Ok(())
+    }
+
+    pub fn save_geojson(
+        matches: &[Match],
+        config: &Config,
+        path: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        fs::write(Path::new(path), MatchExporter::to_geojson(matches, config)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchExporter;
+    use crate::image_scanner::config::Config;
+    use crate::image_scanner::coordinate::Coordinate;
+    use crate::image_scanner::pattern::Pattern;
+    use crate::image_scanner::scan_match::Match;
+    use crate::test_utils::TempDirectory;
+    use image::Rgba;
+
+    fn example_matches() -> Vec<Match> {
+        let found_pattern = Pattern::new_from_coordinates(vec![
+            Coordinate::new(10, 20),
+            Coordinate::new(11, 20),
+            Coordinate::new(11, 22),
+        ]);
+
+        vec![Match::new(
+            "crewmate",
+            &found_pattern,
+            vec![Rgba([255, 69, 0, 255])],
+            1.0,
+            Coordinate::new(-1500, -1000),
+        )]
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let json_lines =
+            MatchExporter::to_json_lines(&example_matches(), &Config::new_default()).unwrap();
+        let lines: Vec<serde_json::Value> = json_lines
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["config"]["searched_color"], "#00000000");
+        assert_eq!(lines[1]["pattern_name"], "crewmate");
+        assert_eq!(lines[1]["canvas_origin"]["x"], -1500);
+        assert_eq!(lines[1]["image_bounding_box"]["min_x"], 10);
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = MatchExporter::to_csv(&example_matches()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "pattern_name,x,y,width,height,rotation,mirrored,scale,colors,score,pixel_count"
+        );
+        assert_eq!(
+            lines[1],
+            "crewmate,-1490,-980,2,3,None,false,1,#FF4500,1.0,3"
+        );
+    }
+
+    #[test]
+    fn test_save_csv_reads_back() {
+        let directory = TempDirectory::new("save_csv");
+        let path = directory.join("matches.csv");
+
+        MatchExporter::save_csv(&example_matches(), &Config::new_default(), &path).unwrap();
+
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        let records: Vec<csv::StringRecord> =
+            reader.records().map(|record| record.unwrap()).collect();
+        assert_eq!(&reader.headers().unwrap()[0], "pattern_name");
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][0], "crewmate");
+        assert_eq!(&records[0][1], "-1490");
+
+        let header: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(directory.join("matches.config.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(header["config"]["searched_color"], "#00000000");
+    }
+
+    #[test]
+    fn test_geojson_polygon_in_canvas_coordinates() {
+        let geojson =
+            MatchExporter::to_geojson(&example_matches(), &Config::new_default()).unwrap();
+        let collection: serde_json::Value = serde_json::from_str(&geojson).unwrap();
+        let feature = &collection["features"][0];
+
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["config"]["pattern_scales"][0], 1);
+        assert_eq!(feature["properties"]["pattern_name"], "crewmate");
+        assert_eq!(
+            feature["geometry"]["coordinates"][0],
+            serde_json::json!([
+                [-1490, -980],
+                [-1488, -980],
+                [-1488, -977],
+                [-1490, -977],
+                [-1490, -980]
+            ])
+        );
+    }
+}
@@ -1,10 +1,41 @@
+pub use bounding_box::BoundingBox;
+pub use color_utils::{AlphaHandling, ColorMatcher, ColorMetric, ColorUtils, PLACE_2023_PALETTE};
+pub use compiled_pattern::CompiledPattern;
 pub use config::Config;
+pub use connected_components::{ComponentLabeling, ConnectedComponent};
+pub use coordinate::Coordinate;
+pub use fuzzy_match::FuzzyMatch;
 pub use image_scanner::ImageScanner;
+pub use image_utils::ImageUtils;
+pub use match_export::MatchExporter;
+pub use multi_color_pattern::{ColorRole, MultiColorPattern, RoleColor, RoleMapping};
+pub use neighborhood::{BorderRule, Connectivity};
+pub use pattern::Pattern;
+pub use pattern_format::{PatternDefinition, PatternFormatError, PatternIO, RoleDefinition};
+pub use pattern_library::{LibraryEntry, PatternLibrary};
+pub use pattern_variant::{PatternVariant, Rotation};
+pub use scan_error::ScanError;
+pub use scan_match::Match;
+pub use shape_frequency::{ShapeFrequency, ShapeOccurrence};
 
+mod bounding_box;
 mod color_utils;
+mod compiled_pattern;
 mod config;
+mod connected_components;
 mod coordinate;
+mod fuzzy_match;
 mod image_scanner;
 mod image_utils;
+mod match_export;
+mod multi_color_pattern;
+mod neighborhood;
 mod pattern;
+mod pattern_format;
+mod pattern_library;
+mod pattern_variant;
+mod scan_error;
 mod scan_image;
+mod scan_match;
+mod scan_multi_color;
+mod shape_frequency;
@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+
+use image::{DynamicImage, GenericImageView, Rgba};
+
+use super::{
+    color_utils::ColorUtils, coordinate::Coordinate, pattern::Pattern,
+    pattern_variant::PatternVariant,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoleColor {
+    /// Any color, as long as it is uniform within the role.
+    Free,
+    Fixed(Rgba<u8>),
+}
+
+#[derive(Debug, Clone)]
+pub struct ColorRole {
+    pub name: String,
+    pub color: RoleColor,
+    pub pattern: Pattern,
+}
+
+/// Template color of a role-mapped image and the role its pixels belong to.
+#[derive(Debug, Clone)]
+pub struct RoleMapping {
+    pub template_color: Rgba<u8>,
+    pub name: String,
+    pub color: RoleColor,
+}
+
+/// Pattern made of several color roles, every role has to be uniformly colored within a match.
+#[derive(Debug, Clone)]
+pub struct MultiColorPattern {
+    roles: Vec<ColorRole>,
+    /// Pairs of role indices that must not share a color.
+    distinct_roles: Vec<(usize, usize)>,
+}
+
+impl MultiColorPattern {
+    pub fn new(roles: Vec<ColorRole>) -> Self {
+        MultiColorPattern {
+            roles,
+            distinct_roles: Vec::new(),
+        }
+    }
+
+    pub fn new_with_distinct_roles(
+        roles: Vec<ColorRole>,
+        distinct_roles: Vec<(usize, usize)>,
+    ) -> Self {
+        MultiColorPattern {
+            roles,
+            distinct_roles,
+        }
+    }
+
+    /// Pixels matching a template color of `role_mappings` belong to its role, others are ignored.
+    pub fn from_role_image(
+        image: DynamicImage,
+        role_mappings: &[RoleMapping],
+        tolerance: u8,
+    ) -> Self {
+        let mut role_coordinates: Vec<Vec<Coordinate>> = vec![Vec::new(); role_mappings.len()];
+
+        for (x, y, pixel_color) in image.pixels() {
+            let role_index = role_mappings.iter().position(|mapping| {
+                ColorUtils::equal_with_tolerance(mapping.template_color, pixel_color, tolerance)
+            });
+
+            if let Some(role_index) = role_index {
+                role_coordinates[role_index].push(Coordinate::new(x as i32, y as i32));
+            }
+        }
+
+        let roles = role_mappings
+            .iter()
+            .zip(role_coordinates)
+            .map(|(mapping, coordinates)| ColorRole {
+                name: mapping.name.clone(),
+                color: mapping.color,
+                pattern: Pattern::new_from_coordinates(coordinates),
+            })
+            .collect();
+
+        MultiColorPattern::new(roles)
+    }
+
+    /// Requires two roles to be matched with different colors, unknown role names are ignored.
+    pub fn require_distinct(&mut self, first_role: &str, second_role: &str) {
+        let first_index = self.role_index(first_role);
+        let second_index = self.role_index(second_role);
+
+        if let (Some(first_index), Some(second_index)) = (first_index, second_index) {
+            self.distinct_roles.push((first_index, second_index));
+        }
+    }
+
+    pub fn role_index(&self, name: &str) -> Option<usize> {
+        self.roles.iter().position(|role| role.name == name)
+    }
+
+    pub fn get_roles(&self) -> &Vec<ColorRole> {
+        &self.roles
+    }
+
+    pub fn get_distinct_roles(&self) -> &Vec<(usize, usize)> {
+        &self.distinct_roles
+    }
+
+    pub fn get_variant(&self) -> PatternVariant {
+        self.roles
+            .first()
+            .map(|role| role.pattern.get_variant())
+            .unwrap_or_else(PatternVariant::identity)
+    }
+
+    /// Single-color pattern covering the pixels of every role.
+    pub fn to_pattern(&self) -> Pattern {
+        let mut coordinates: Vec<Coordinate> = self
+            .roles
+            .iter()
+            .flat_map(|role| role.pattern.get_coordinates().iter().copied())
+            .collect();
+        coordinates.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+
+        Pattern::new_with_variant(coordinates, self.get_variant())
+    }
+
+    /// All roles moved together so the top-left corner of their bounding box is at (0, 0).
+    pub fn normalized(&self) -> MultiColorPattern {
+        let bounding_box = match self.to_pattern().bounding_box() {
+            Some(bounding_box) => bounding_box,
+            None => return self.clone(),
+        };
+
+        MultiColorPattern {
+            roles: self
+                .roles
+                .iter()
+                .map(|role| ColorRole {
+                    name: role.name.clone(),
+                    color: role.color,
+                    pattern: role
+                        .pattern
+                        .translated(-bounding_box.min_x, -bounding_box.min_y),
+                })
+                .collect(),
+            distinct_roles: self.distinct_roles.clone(),
+        }
+    }
+
+    pub fn get_window_size(&self) -> (u32, u32) {
+        self.to_pattern().get_window_size()
+    }
+
+    /// All roles transformed by `variant`, moved back together so the top-left corner is at (0, 0).
+    pub fn transformed(&self, variant: PatternVariant) -> MultiColorPattern {
+        let transformed_roles: Vec<Vec<Coordinate>> = self
+            .roles
+            .iter()
+            .map(|role| {
+                role.pattern
+                    .get_coordinates()
+                    .iter()
+                    .map(|coordinate| variant.apply(coordinate))
+                    .collect()
+            })
+            .collect();
+
+        let all_coordinates = transformed_roles.iter().flatten();
+        let min_x = all_coordinates.clone().map(|c| c.x).min().unwrap_or(0);
+        let min_y = all_coordinates.map(|c| c.y).min().unwrap_or(0);
+
+        let roles = self
+            .roles
+            .iter()
+            .zip(transformed_roles)
+            .map(|(role, coordinates)| {
+                let mut coordinates: Vec<Coordinate> = coordinates
+                    .into_iter()
+                    .map(|c| Coordinate::new(c.x - min_x, c.y - min_y))
+                    .collect();
+                coordinates.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+
+                ColorRole {
+                    name: role.name.clone(),
+                    color: role.color,
+                    pattern: Pattern::new_with_variant(coordinates, variant),
+                }
+            })
+            .collect();
+
+        MultiColorPattern {
+            roles,
+            distinct_roles: self.distinct_roles.clone(),
+        }
+    }
+
+    /// Rotated and mirrored variants, variants identical to an earlier one are skipped.
+    pub fn variants(&self) -> Vec<MultiColorPattern> {
+        let mut seen_shapes: HashSet<Vec<Vec<Coordinate>>> = HashSet::new();
+        let mut variants = Vec::new();
+
+        for variant in PatternVariant::all() {
+            let pattern = self.transformed(variant);
+            let shape = pattern
+                .roles
+                .iter()
+                .map(|role| role.pattern.get_coordinates().clone())
+                .collect();
+
+            if seen_shapes.insert(shape) {
+                variants.push(pattern);
+            }
+        }
+
+        variants
+    }
+
+    pub fn upscaled(&self, scale: u32) -> MultiColorPattern {
+        MultiColorPattern {
+            roles: self
+                .roles
+                .iter()
+                .map(|role| ColorRole {
+                    name: role.name.clone(),
+                    color: role.color,
+                    pattern: role.pattern.upscaled(scale),
+                })
+                .collect(),
+            distinct_roles: self.distinct_roles.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MultiColorPattern, RoleColor, RoleMapping};
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    const BODY: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const VISOR: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    fn template() -> DynamicImage {
+        let mut image = RgbaImage::from_pixel(3, 2, Rgba([255, 255, 255, 255]));
+        image.put_pixel(0, 0, BODY);
+        image.put_pixel(0, 1, BODY);
+        image.put_pixel(1, 1, BODY);
+        image.put_pixel(1, 0, VISOR);
+        image.put_pixel(2, 0, VISOR);
+        DynamicImage::ImageRgba8(image)
+    }
+
+    fn role_mappings() -> Vec<RoleMapping> {
+        vec![
+            RoleMapping {
+                template_color: BODY,
+                name: "body".to_string(),
+                color: RoleColor::Free,
+            },
+            RoleMapping {
+                template_color: VISOR,
+                name: "visor".to_string(),
+                color: RoleColor::Fixed(Rgba([148, 179, 255, 255])),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_from_role_image() {
+        let mut pattern = MultiColorPattern::from_role_image(template(), &role_mappings(), 0);
+        pattern.require_distinct("body", "visor");
+        pattern.require_distinct("body", "unknown");
+
+        assert_eq!(pattern.get_roles()[0].pattern.get_coordinates().len(), 3);
+        assert_eq!(pattern.get_roles()[1].pattern.get_coordinates().len(), 2);
+        assert_eq!(pattern.get_distinct_roles(), &vec![(0, 1)]);
+        assert_eq!(pattern.get_window_size(), (3, 2));
+    }
+
+    #[test]
+    fn test_transformed_keeps_roles_aligned() {
+        let pattern = MultiColorPattern::from_role_image(template(), &role_mappings(), 0);
+
+        let variants = pattern.variants();
+        let rotated = &variants[1];
+
+        assert_eq!(variants.len(), 8);
+        assert_eq!(rotated.get_window_size(), (2, 3));
+        assert_eq!(rotated.to_pattern().get_coordinates().len(), 5);
+    }
+}
@@ -0,0 +1,94 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde_derive::Serialize)]
+pub enum Connectivity {
+    /// Only horizontal and vertical neighbors.
+    Four,
+    /// Horizontal, vertical and diagonal neighbors.
+    Eight,
+}
+
+impl Connectivity {
+    /// Offsets of every pixel reachable in at most `distance` steps, excluding the center.
+    pub fn offsets(&self, distance: u32) -> Vec<(i32, i32)> {
+        let distance = distance as i32;
+        let mut offsets = Vec::new();
+
+        for offset_y in -distance..=distance {
+            for offset_x in -distance..=distance {
+                let steps = match self {
+                    Connectivity::Four => offset_x.abs() + offset_y.abs(),
+                    Connectivity::Eight => offset_x.abs().max(offset_y.abs()),
+                };
+
+                if steps != 0 && steps <= distance {
+                    offsets.push((offset_x, offset_y));
+                }
+            }
+        }
+
+        offsets
+    }
+}
+
+/// Which pixels around a pattern must not have the pattern color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde_derive::Serialize)]
+pub enum BorderRule {
+    /// Matches are not checked against their surroundings.
+    None,
+    /// Every pixel within `width` steps of the pattern is part of the border.
+    Ring {
+        connectivity: Connectivity,
+        width: u32,
+    },
+}
+
+impl BorderRule {
+    pub fn new(connectivity: Connectivity, width: u32) -> Self {
+        BorderRule::Ring {
+            connectivity,
+            width,
+        }
+    }
+
+    pub fn new_default() -> Self {
+        BorderRule::new(Connectivity::Eight, 1)
+    }
+
+    /// How far the border reaches out of the pattern window.
+    pub fn width(&self) -> u32 {
+        match self {
+            BorderRule::None => 0,
+            BorderRule::Ring { width, .. } => *width,
+        }
+    }
+
+    pub fn offsets(&self) -> Vec<(i32, i32)> {
+        match self {
+            BorderRule::None => Vec::new(),
+            BorderRule::Ring {
+                connectivity,
+                width,
+            } => connectivity.offsets(*width),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BorderRule, Connectivity};
+
+    #[test]
+    fn test_connectivity_offsets() {
+        assert_eq!(Connectivity::Four.offsets(1).len(), 4);
+        assert_eq!(Connectivity::Eight.offsets(1).len(), 8);
+        assert_eq!(Connectivity::Four.offsets(2).len(), 12);
+        assert_eq!(Connectivity::Eight.offsets(2).len(), 24);
+        assert!(!Connectivity::Four.offsets(1).contains(&(1, 1)));
+    }
+
+    #[test]
+    fn test_border_rule_width() {
+        assert_eq!(BorderRule::None.width(), 0);
+        assert!(BorderRule::None.offsets().is_empty());
+        assert_eq!(BorderRule::new(Connectivity::Four, 3).width(), 3);
+    }
+}
@@ -1,24 +1,93 @@
 use std::collections::HashSet;
 
-use image::{DynamicImage, GenericImageView, Rgba};
-
-use super::{color_utils::ColorUtils, coordinate::Coordinate};
-
-#[derive(Debug)]
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+use super::{
+    bounding_box::BoundingBox,
+    color_utils::{AlphaHandling, ColorMatcher, ColorMetric},
+    connected_components::{flood_fill, UNLABELED},
+    coordinate::Coordinate,
+    neighborhood::{BorderRule, Connectivity},
+    pattern_variant::PatternVariant,
+};
+
+#[derive(Debug, Clone)]
 pub struct Pattern {
+    /// Cells that must have the pattern color.
     coordinates: Vec<Coordinate>,
+    /// Cells that must not have the pattern color, checked like the border.
+    must_not_coordinates: Vec<Coordinate>,
+    /// Cells that are never checked, they are also left out of the border.
+    dont_care_coordinates: Vec<Coordinate>,
+    /// Same cells as `coordinates`, for constant time lookups.
+    coordinate_set: HashSet<Coordinate>,
+    variant: PatternVariant,
 }
 
 impl Pattern {
     pub fn new_from_coordinates(coordinates: Vec<Coordinate>) -> Self {
-        Pattern { coordinates }
+        Pattern::new_with_variant(coordinates, PatternVariant::identity())
+    }
+
+    pub fn new_with_variant(coordinates: Vec<Coordinate>, variant: PatternVariant) -> Self {
+        Pattern::from_parts(coordinates, Vec::new(), Vec::new(), variant)
+    }
+
+    pub fn new_masked(
+        coordinates: Vec<Coordinate>,
+        must_not_coordinates: Vec<Coordinate>,
+        dont_care_coordinates: Vec<Coordinate>,
+    ) -> Self {
+        Pattern::from_parts(
+            coordinates,
+            must_not_coordinates,
+            dont_care_coordinates,
+            PatternVariant::identity(),
+        )
+    }
+
+    fn from_parts(
+        coordinates: Vec<Coordinate>,
+        must_not_coordinates: Vec<Coordinate>,
+        dont_care_coordinates: Vec<Coordinate>,
+        variant: PatternVariant,
+    ) -> Self {
+        Pattern {
+            coordinate_set: coordinates.iter().copied().collect(),
+            coordinates,
+            must_not_coordinates,
+            dont_care_coordinates,
+            variant,
+        }
     }
 
     pub fn get_coordinates(&self) -> &Vec<Coordinate> {
         &self.coordinates
     }
 
+    pub fn get_must_not_coordinates(&self) -> &Vec<Coordinate> {
+        &self.must_not_coordinates
+    }
+
+    pub fn get_dont_care_coordinates(&self) -> &Vec<Coordinate> {
+        &self.dont_care_coordinates
+    }
+
+    pub fn get_variant(&self) -> PatternVariant {
+        self.variant
+    }
+
+    /// Compares RGB channels with `tolerance`, see `from_image_with_matcher`.
     pub fn from_image(image: DynamicImage, search_color: Rgba<u8>, tolerance: u8) -> Self {
+        Pattern::from_image_with_matcher(image, search_color, &channel_delta_matcher(tolerance))
+    }
+
+    /// Pixels that `color_matcher` matches with `search_color`.
+    pub fn from_image_with_matcher(
+        image: DynamicImage,
+        search_color: Rgba<u8>,
+        color_matcher: &ColorMatcher,
+    ) -> Self {
         let (img_width, img_height) = image.dimensions();
 
         let mut coordinates: Vec<Coordinate> = Vec::new();
@@ -27,42 +96,319 @@ impl Pattern {
             for x in 0..img_width as i32 {
                 let pixel_color = image.get_pixel(x as u32, y as u32);
 
-                let is_equal =
-                    ColorUtils::equal_with_tolerance(search_color, pixel_color, tolerance);
-
-                if is_equal {
+                if color_matcher.matches(search_color, pixel_color) {
                     coordinates.push(Coordinate::new(x, y))
                 }
             }
         }
 
-        Pattern { coordinates }
+        Pattern::new_from_coordinates(coordinates)
     }
 
-    pub fn get_window_size(&self) -> (u32, u32) {
-        let mut highest_x = 0;
-        let mut highest_y = 0;
+    /// Compares RGB channels with `tolerance`, see `from_masked_image_with_matcher`.
+    pub fn from_masked_image(
+        image: DynamicImage,
+        search_color: Rgba<u8>,
+        dont_care_color: Rgba<u8>,
+        tolerance: u8,
+    ) -> Self {
+        Pattern::from_masked_image_with_matcher(
+            image,
+            search_color,
+            dont_care_color,
+            &channel_delta_matcher(tolerance),
+        )
+    }
+
+    /// Pixels of `search_color` must match, pixels of `dont_care_color` are ignored
+    /// and every other pixel of the image must not have the pattern color.
+    pub fn from_masked_image_with_matcher(
+        image: DynamicImage,
+        search_color: Rgba<u8>,
+        dont_care_color: Rgba<u8>,
+        color_matcher: &ColorMatcher,
+    ) -> Self {
+        let (img_width, img_height) = image.dimensions();
+
+        let mut coordinates = Vec::new();
+        let mut must_not_coordinates = Vec::new();
+        let mut dont_care_coordinates = Vec::new();
 
-        for i in &self.coordinates {
-            if i.x > highest_x {
-                highest_x = i.x;
+        for y in 0..img_height as i32 {
+            for x in 0..img_width as i32 {
+                let pixel_color = image.get_pixel(x as u32, y as u32);
+                let coordinate = Coordinate::new(x, y);
+
+                if color_matcher.matches(search_color, pixel_color) {
+                    coordinates.push(coordinate);
+                } else if color_matcher.matches(dont_care_color, pixel_color) {
+                    dont_care_coordinates.push(coordinate);
+                } else {
+                    must_not_coordinates.push(coordinate);
+                }
             }
-            if i.y > highest_y {
-                highest_y = i.y;
+        }
+
+        Pattern::new_masked(coordinates, must_not_coordinates, dont_care_coordinates)
+    }
+
+    /// Region of pixels connected to (`x`, `y`) that `color_matcher` matches with its color,
+    /// moved so its top-left corner is at (0, 0). None when the coordinate is outside of the image.
+    pub fn from_flood_fill(
+        image: &DynamicImage,
+        x: u32,
+        y: u32,
+        connectivity: Connectivity,
+        color_matcher: &ColorMatcher,
+    ) -> Option<Self> {
+        if x >= image.width() || y >= image.height() {
+            return None;
+        }
+
+        let pixels = image.to_rgba8();
+        let mut labels = vec![UNLABELED; pixels.len() / 4];
+
+        let coordinates = flood_fill(
+            &pixels,
+            Coordinate::new(x as i32, y as i32),
+            &connectivity.offsets(1),
+            color_matcher,
+            &mut labels,
+            0,
+        );
+
+        Some(Pattern::new_from_coordinates(coordinates).transformed(PatternVariant::identity()))
+    }
+
+    /// Pattern cells drawn in `color` on a `background` of the window size, the image
+    /// can be saved and loaded back with `from_image`.
+    pub fn to_image(&self, color: Rgba<u8>, background: Rgba<u8>) -> DynamicImage {
+        let normalized = self.normalized();
+        let (width, height) = normalized.get_window_size();
+        let mut image = RgbaImage::from_pixel(width, height, background);
+
+        for coordinate in &normalized.coordinates {
+            image.put_pixel(coordinate.x as u32, coordinate.y as u32, color);
+        }
+
+        DynamicImage::ImageRgba8(image)
+    }
+
+    /// Pattern transformed by `variant` and moved back so the top-left corner
+    /// of its mask is at (0, 0).
+    pub fn transformed(&self, variant: PatternVariant) -> Pattern {
+        let transform = |coordinates: &[Coordinate]| -> Vec<Coordinate> {
+            coordinates
+                .iter()
+                .map(|coordinate| variant.apply(coordinate))
+                .collect()
+        };
+
+        let coordinates = transform(&self.coordinates);
+        let must_not_coordinates = transform(&self.must_not_coordinates);
+        let dont_care_coordinates = transform(&self.dont_care_coordinates);
+
+        let mask = || {
+            coordinates
+                .iter()
+                .chain(&must_not_coordinates)
+                .chain(&dont_care_coordinates)
+        };
+        let min_x = mask().map(|c| c.x).min().unwrap_or(0);
+        let min_y = mask().map(|c| c.y).min().unwrap_or(0);
+
+        let normalize = |coordinates: &[Coordinate]| -> Vec<Coordinate> {
+            let mut normalized: Vec<Coordinate> = coordinates
+                .iter()
+                .map(|coordinate| Coordinate::new(coordinate.x - min_x, coordinate.y - min_y))
+                .collect();
+            normalized.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+            normalized
+        };
+
+        Pattern::from_parts(
+            normalize(&coordinates),
+            normalize(&must_not_coordinates),
+            normalize(&dont_care_coordinates),
+            variant,
+        )
+    }
+
+    /// Every rotated and mirrored variant of the pattern, variants producing
+    /// the same shape as an earlier one (symmetric patterns) are skipped.
+    pub fn variants(&self) -> Vec<Pattern> {
+        let mut seen_shapes = HashSet::new();
+        let mut variants = Vec::new();
+
+        for variant in PatternVariant::all() {
+            let pattern = self.transformed(variant);
+
+            let shape = (
+                pattern.coordinates.clone(),
+                pattern.must_not_coordinates.clone(),
+                pattern.dont_care_coordinates.clone(),
+            );
+
+            if seen_shapes.insert(shape) {
+                variants.push(pattern);
             }
         }
 
-        (highest_x as u32 + 1, highest_y as u32 + 1)
+        variants
     }
 
-    pub fn generate_coordinates_of_adjacent_pixels(&self) -> Vec<Coordinate> {
-        let mut adjacent_coordinates = HashSet::new();
+    /// Pattern drawn `scale` times bigger, every cell becomes a `scale` x `scale` block.
+    pub fn upscaled(&self, scale: u32) -> Pattern {
+        let scale = scale.max(1) as i32;
+
+        let upscale = |coordinates: &[Coordinate]| -> Vec<Coordinate> {
+            let mut upscaled = Vec::with_capacity(coordinates.len() * (scale * scale) as usize);
+
+            for coordinate in coordinates {
+                for offset_y in 0..scale {
+                    for offset_x in 0..scale {
+                        upscaled.push(Coordinate::new(
+                            coordinate.x * scale + offset_x,
+                            coordinate.y * scale + offset_y,
+                        ));
+                    }
+                }
+            }
+            upscaled.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+            upscaled
+        };
+
+        Pattern::from_parts(
+            upscale(&self.coordinates),
+            upscale(&self.must_not_coordinates),
+            upscale(&self.dont_care_coordinates),
+            self.variant.with_scale(self.variant.scale * scale as u32),
+        )
+    }
+
+    /// Every cell of the pattern, must-not and don't-care cells included.
+    fn mask_coordinates(&self) -> impl Iterator<Item = &Coordinate> {
+        self.coordinates
+            .iter()
+            .chain(&self.must_not_coordinates)
+            .chain(&self.dont_care_coordinates)
+    }
+
+    /// Bounding box of the pattern together with its must-not and don't-care cells,
+    /// None for an empty pattern.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_coordinates(self.mask_coordinates())
+    }
+
+    pub fn get_window_size(&self) -> (u32, u32) {
+        self.bounding_box()
+            .map(|bounding_box| (bounding_box.width(), bounding_box.height()))
+            .unwrap_or((0, 0))
+    }
+
+    pub fn pixel_count(&self) -> usize {
+        self.coordinates.len()
+    }
+
+    /// Average position of the pattern cells, None for an empty pattern.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        if self.coordinates.is_empty() {
+            return None;
+        }
+
+        let count = self.coordinates.len() as f64;
+        let sum_x: f64 = self.coordinates.iter().map(|c| c.x as f64).sum();
+        let sum_y: f64 = self.coordinates.iter().map(|c| c.y as f64).sum();
+
+        Some((sum_x / count, sum_y / count))
+    }
+
+    /// Every cell moved by (`offset_x`, `offset_y`), the variant is kept.
+    pub fn translated(&self, offset_x: i32, offset_y: i32) -> Pattern {
+        let translate = |coordinates: &[Coordinate]| -> Vec<Coordinate> {
+            coordinates
+                .iter()
+                .map(|coordinate| Coordinate::new(coordinate.x + offset_x, coordinate.y + offset_y))
+                .collect()
+        };
+
+        Pattern::from_parts(
+            translate(&self.coordinates),
+            translate(&self.must_not_coordinates),
+            translate(&self.dont_care_coordinates),
+            self.variant,
+        )
+    }
+
+    /// Pattern moved so the top-left corner of its bounding box is at (0, 0).
+    pub fn normalized(&self) -> Pattern {
+        match self.bounding_box() {
+            Some(bounding_box) => self.translated(-bounding_box.min_x, -bounding_box.min_y),
+            None => self.clone(),
+        }
+    }
+
+    /// Cells of either pattern. Set operations only look at the pattern cells, the result
+    /// has no must-not or don't-care cells.
+    pub fn union(&self, other: &Pattern) -> Pattern {
+        let mut coordinates = self.coordinates.clone();
+        coordinates.extend(
+            other
+                .coordinates
+                .iter()
+                .filter(|coordinate| !self.contains_coordinate(coordinate)),
+        );
+
+        Pattern::from_sorted_coordinates(coordinates)
+    }
+
+    pub fn intersection(&self, other: &Pattern) -> Pattern {
+        Pattern::from_sorted_coordinates(
+            self.coordinates
+                .iter()
+                .filter(|coordinate| other.contains_coordinate(coordinate))
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// Cells of this pattern that are not in `other`.
+    pub fn difference(&self, other: &Pattern) -> Pattern {
+        Pattern::from_sorted_coordinates(
+            self.coordinates
+                .iter()
+                .filter(|coordinate| !other.contains_coordinate(coordinate))
+                .copied()
+                .collect(),
+        )
+    }
+
+    fn from_sorted_coordinates(mut coordinates: Vec<Coordinate>) -> Pattern {
+        coordinates.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+        coordinates.dedup();
+
+        Pattern::new_from_coordinates(coordinates)
+    }
+
+    /// Border ring around the pattern, shaped by `border_rule`. Don't-care cells are
+    /// left out and must-not cells are always included.
+    pub fn generate_coordinates_of_adjacent_pixels(
+        &self,
+        border_rule: &BorderRule,
+    ) -> Vec<Coordinate> {
+        let border_offsets = border_rule.offsets();
+        let dont_care_coordinates: HashSet<&Coordinate> =
+            self.dont_care_coordinates.iter().collect();
+        let mut adjacent_coordinates: HashSet<Coordinate> =
+            self.must_not_coordinates.iter().copied().collect();
 
         for coord in &self.coordinates {
-            for offset in SURROUNDING_OFFSETS {
+            for offset in &border_offsets {
                 let new_coordinate = Coordinate::new(coord.x + offset.0, coord.y + offset.1);
 
-                if !self.contains_coordinate(&new_coordinate) {
+                if !self.contains_coordinate(&new_coordinate)
+                    && !dont_care_coordinates.contains(&new_coordinate)
+                {
                     adjacent_coordinates.insert(new_coordinate);
                 }
             }
@@ -72,40 +418,69 @@ impl Pattern {
     }
 
     pub fn contains_coordinate(&self, coordinate: &Coordinate) -> bool {
-        self.coordinates.contains(coordinate)
+        self.coordinate_set.contains(coordinate)
+    }
+
+    /// Shape moved to the origin, with `rotation_invariant` the smallest of all rotated
+    /// and mirrored variants is picked so every orientation has the same canonical form.
+    pub fn canonical(&self, rotation_invariant: bool) -> Pattern {
+        let canonical = if rotation_invariant {
+            self.variants()
+                .into_iter()
+                .min_by(|first, second| first.coordinates.cmp(&second.coordinates))
+                .unwrap_or_else(|| self.transformed(PatternVariant::identity()))
+        } else {
+            self.transformed(PatternVariant::identity())
+        };
+
+        Pattern::new_from_coordinates(canonical.coordinates)
+    }
+
+    /// FNV-1a hash of the coordinates, stable between runs and builds.
+    pub fn shape_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET_BASIS;
+
+        for coordinate in &self.coordinates {
+            for byte in coordinate
+                .x
+                .to_le_bytes()
+                .into_iter()
+                .chain(coordinate.y.to_le_bytes())
+            {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        hash
     }
 }
 
-const SURROUNDING_OFFSETS: [(i32, i32); 8] = [
-    (LEFT, TOP),
-    (CENTER, TOP),
-    (RIGHT, TOP),
-    (LEFT, CENTER),
-    (RIGHT, CENTER),
-    (LEFT, BOTTOM),
-    (CENTER, BOTTOM),
-    (RIGHT, BOTTOM),
-];
-
-const LEFT: i32 = -1;
-const RIGHT: i32 = 1;
-const TOP: i32 = -1;
-const BOTTOM: i32 = 1;
-const CENTER: i32 = 0;
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn channel_delta_matcher(tolerance: u8) -> ColorMatcher {
+    ColorMatcher::new(
+        ColorMetric::ChannelDelta,
+        tolerance as f32,
+        AlphaHandling::Ignore,
+    )
+}
 
 #[cfg(test)]
 mod tests {
     use super::{Coordinate, Pattern};
-    use image::{io::Reader as ImageReader, Rgba};
+    use crate::image_scanner::color_utils::{AlphaHandling, ColorMatcher, ColorMetric};
+    use crate::image_scanner::neighborhood::{BorderRule, Connectivity};
+    use crate::image_scanner::pattern_variant::{PatternVariant, Rotation};
+    use crate::test_utils::{load_image, COLOR_MATCHER};
+    use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
 
     #[test]
     fn test_from_image() {
         // Load the image from the specified path
         let image_path = "assets/images/crewmate.png";
-        let image = ImageReader::open(image_path)
-            .expect("Failed to open image")
-            .decode()
-            .expect("Failed to decode image");
+        let image = load_image(image_path);
 
         // Define the expected coordinates
         let expected_coordinates = vec![
@@ -127,17 +502,40 @@ mod tests {
         assert_eq!(pattern.coordinates, expected_coordinates);
     }
 
+    #[test]
+    fn test_from_image_with_matcher_uses_alpha_handling() {
+        let mut image = RgbaImage::from_pixel(2, 1, Rgba([0, 0, 0, 255]));
+        image.put_pixel(1, 0, Rgba([255, 255, 255, 0]));
+        let image = DynamicImage::ImageRgba8(image);
+        let transparent = Rgba([0, 0, 0, 0]);
+        let alpha_matcher = ColorMatcher::new(
+            ColorMetric::ChannelDelta,
+            1.0,
+            AlphaHandling::Compare { tolerance: 0 },
+        );
+
+        let ignoring_alpha = Pattern::from_image(image.clone(), transparent, 1);
+        let comparing_alpha = Pattern::from_image_with_matcher(image, transparent, &alpha_matcher);
+
+        assert_eq!(
+            ignoring_alpha.get_coordinates(),
+            &vec![Coordinate::new(0, 0)]
+        );
+        assert_eq!(
+            comparing_alpha.get_coordinates(),
+            &vec![Coordinate::new(1, 0)]
+        );
+    }
+
     #[test]
     fn test_get_coordinates_bounds() {
-        let pattern = Pattern {
-            coordinates: vec![
-                Coordinate { x: 2, y: 5 },
-                Coordinate { x: 4, y: 3 },
-                Coordinate { x: 1, y: 6 },
-            ],
-        };
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 2, y: 5 },
+            Coordinate { x: 4, y: 3 },
+            Coordinate { x: 1, y: 6 },
+        ]);
 
-        let expected_bounds = (5, 7);
+        let expected_bounds = (4, 4);
 
         let actual_bounds = pattern.get_window_size();
 
@@ -146,39 +544,36 @@ mod tests {
 
     #[test]
     fn test_generate_pattern_with_adjacent_pixels() {
-        let pattern = Pattern {
-            coordinates: vec![
-                Coordinate { x: 2, y: 5 },
-                Coordinate { x: 4, y: 3 },
-                Coordinate { x: 1, y: 6 },
-            ],
-        };
-
-        let expected_adjacent_pattern = Pattern {
-            coordinates: vec![
-                Coordinate { x: 0, y: 5 },
-                Coordinate { x: 0, y: 6 },
-                Coordinate { x: 0, y: 7 },
-                Coordinate { x: 1, y: 4 },
-                Coordinate { x: 1, y: 5 },
-                Coordinate { x: 1, y: 7 },
-                Coordinate { x: 2, y: 4 },
-                Coordinate { x: 2, y: 6 },
-                Coordinate { x: 2, y: 7 },
-                Coordinate { x: 3, y: 2 },
-                Coordinate { x: 3, y: 3 },
-                Coordinate { x: 3, y: 4 },
-                Coordinate { x: 3, y: 5 },
-                Coordinate { x: 3, y: 6 },
-                Coordinate { x: 4, y: 2 },
-                Coordinate { x: 4, y: 4 },
-                Coordinate { x: 5, y: 2 },
-                Coordinate { x: 5, y: 3 },
-                Coordinate { x: 5, y: 4 },
-            ],
-        };
-
-        let mut actual_coordinates = pattern.generate_coordinates_of_adjacent_pixels();
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 2, y: 5 },
+            Coordinate { x: 4, y: 3 },
+            Coordinate { x: 1, y: 6 },
+        ]);
+
+        let expected_adjacent_pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 5 },
+            Coordinate { x: 0, y: 6 },
+            Coordinate { x: 0, y: 7 },
+            Coordinate { x: 1, y: 4 },
+            Coordinate { x: 1, y: 5 },
+            Coordinate { x: 1, y: 7 },
+            Coordinate { x: 2, y: 4 },
+            Coordinate { x: 2, y: 6 },
+            Coordinate { x: 2, y: 7 },
+            Coordinate { x: 3, y: 2 },
+            Coordinate { x: 3, y: 3 },
+            Coordinate { x: 3, y: 4 },
+            Coordinate { x: 3, y: 5 },
+            Coordinate { x: 3, y: 6 },
+            Coordinate { x: 4, y: 2 },
+            Coordinate { x: 4, y: 4 },
+            Coordinate { x: 5, y: 2 },
+            Coordinate { x: 5, y: 3 },
+            Coordinate { x: 5, y: 4 },
+        ]);
+
+        let mut actual_coordinates =
+            pattern.generate_coordinates_of_adjacent_pixels(&BorderRule::new_default());
         let mut expected_coordinates = expected_adjacent_pattern.coordinates;
 
         actual_coordinates.sort();
@@ -187,15 +582,41 @@ mod tests {
         assert_eq!(actual_coordinates, expected_coordinates);
     }
 
+    #[test]
+    fn test_adjacent_pixels_follow_border_rule() {
+        let pattern = Pattern::new_from_coordinates(vec![Coordinate { x: 0, y: 0 }]);
+
+        let mut four_connected = pattern
+            .generate_coordinates_of_adjacent_pixels(&BorderRule::new(Connectivity::Four, 1));
+        four_connected.sort();
+
+        assert_eq!(
+            four_connected,
+            vec![
+                Coordinate { x: -1, y: 0 },
+                Coordinate { x: 0, y: -1 },
+                Coordinate { x: 0, y: 1 },
+                Coordinate { x: 1, y: 0 },
+            ]
+        );
+        assert_eq!(
+            pattern
+                .generate_coordinates_of_adjacent_pixels(&BorderRule::new(Connectivity::Eight, 2))
+                .len(),
+            24
+        );
+        assert!(pattern
+            .generate_coordinates_of_adjacent_pixels(&BorderRule::None)
+            .is_empty());
+    }
+
     #[test]
     fn test_contains_coordinate() {
-        let pattern = Pattern {
-            coordinates: vec![
-                Coordinate { x: 2, y: 5 },
-                Coordinate { x: 4, y: 3 },
-                Coordinate { x: 1, y: 6 },
-            ],
-        };
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 2, y: 5 },
+            Coordinate { x: 4, y: 3 },
+            Coordinate { x: 1, y: 6 },
+        ]);
 
         assert!(pattern.contains_coordinate(&Coordinate { x: 2, y: 5 }));
         assert!(pattern.contains_coordinate(&Coordinate { x: 4, y: 3 }));
@@ -205,4 +626,279 @@ mod tests {
         assert!(!pattern.contains_coordinate(&Coordinate { x: 3, y: 5 }));
         assert!(!pattern.contains_coordinate(&Coordinate { x: 1, y: 1 }));
     }
+
+    #[test]
+    fn test_variants_include_mirrored_crewmate() {
+        let crewmate = load_image("assets/images/crewmate.png");
+
+        let variants = Pattern::from_image(crewmate, Rgba([0, 0, 0, 0]), 0).variants();
+
+        let mirrored_crewmate = vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 0 },
+            Coordinate { x: 2, y: 0 },
+            Coordinate { x: 2, y: 1 },
+            Coordinate { x: 3, y: 1 },
+            Coordinate { x: 0, y: 2 },
+            Coordinate { x: 1, y: 2 },
+            Coordinate { x: 2, y: 2 },
+            Coordinate { x: 3, y: 2 },
+            Coordinate { x: 0, y: 3 },
+            Coordinate { x: 2, y: 3 },
+        ];
+
+        let mirrored_variant = variants
+            .iter()
+            .find(|variant| variant.coordinates == mirrored_crewmate)
+            .unwrap();
+
+        assert_eq!(variants.len(), 8);
+        assert_eq!(
+            mirrored_variant.get_variant(),
+            PatternVariant::new(Rotation::None, true)
+        );
+    }
+
+    #[test]
+    fn test_variants_of_symmetric_pattern() {
+        let square = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 0 },
+            Coordinate { x: 0, y: 1 },
+            Coordinate { x: 1, y: 1 },
+        ]);
+        let line = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 0 },
+            Coordinate { x: 2, y: 0 },
+        ]);
+
+        assert_eq!(square.variants().len(), 1);
+        assert_eq!(line.variants().len(), 2);
+    }
+
+    #[test]
+    fn test_from_masked_image() {
+        let black = Rgba([0, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        let dont_care = Rgba([255, 0, 255, 255]);
+        let image = RgbaImage::from_fn(3, 2, |x, y| match (x, y) {
+            (0, 0) | (1, 1) | (2, 1) => black,
+            (1, 0) => dont_care,
+            _ => white,
+        });
+
+        let pattern =
+            Pattern::from_masked_image(DynamicImage::ImageRgba8(image), black, dont_care, 0);
+
+        assert_eq!(
+            pattern.get_coordinates(),
+            &vec![
+                Coordinate { x: 0, y: 0 },
+                Coordinate { x: 1, y: 1 },
+                Coordinate { x: 2, y: 1 },
+            ]
+        );
+        assert_eq!(
+            pattern.get_must_not_coordinates(),
+            &vec![Coordinate { x: 2, y: 0 }, Coordinate { x: 0, y: 1 }]
+        );
+        assert_eq!(
+            pattern.get_dont_care_coordinates(),
+            &vec![Coordinate { x: 1, y: 0 }]
+        );
+
+        let border = pattern.generate_coordinates_of_adjacent_pixels(&BorderRule::new_default());
+
+        assert!(border.contains(&Coordinate { x: 2, y: 0 }));
+        assert!(border.contains(&Coordinate { x: 0, y: 1 }));
+        assert!(!border.contains(&Coordinate { x: 1, y: 0 }));
+    }
+
+    #[test]
+    fn test_transformed_keeps_mask() {
+        let pattern = Pattern::new_masked(
+            vec![Coordinate { x: 0, y: 0 }, Coordinate { x: 1, y: 0 }],
+            vec![Coordinate { x: 2, y: 0 }],
+            vec![Coordinate { x: 0, y: 1 }],
+        );
+
+        let mirrored = pattern.transformed(PatternVariant::new(Rotation::None, true));
+
+        assert_eq!(
+            mirrored.get_coordinates(),
+            &vec![Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 0 }]
+        );
+        assert_eq!(
+            mirrored.get_must_not_coordinates(),
+            &vec![Coordinate { x: 0, y: 0 }]
+        );
+        assert_eq!(
+            mirrored.get_dont_care_coordinates(),
+            &vec![Coordinate { x: 2, y: 1 }]
+        );
+        assert_eq!(mirrored.get_window_size(), (3, 2));
+        assert_eq!(pattern.upscaled(2).get_dont_care_coordinates().len(), 4);
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let l_shape = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 3, y: 3 },
+            Coordinate { x: 3, y: 4 },
+            Coordinate { x: 4, y: 4 },
+        ]);
+        let rotated_l_shape = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 0 },
+            Coordinate { x: 0, y: 1 },
+        ]);
+
+        assert_eq!(
+            l_shape.canonical(false).get_coordinates(),
+            &vec![
+                Coordinate { x: 0, y: 0 },
+                Coordinate { x: 0, y: 1 },
+                Coordinate { x: 1, y: 1 },
+            ]
+        );
+        assert_ne!(
+            l_shape.canonical(false).shape_hash(),
+            rotated_l_shape.canonical(false).shape_hash()
+        );
+        assert_eq!(
+            l_shape.canonical(true).shape_hash(),
+            rotated_l_shape.canonical(true).shape_hash()
+        );
+    }
+
+    #[test]
+    fn test_from_flood_fill() {
+        let image = load_image("assets/images/crewmate_with_borders.png");
+        let crewmate = Pattern::from_image(
+            load_image("assets/images/crewmate.png"),
+            Rgba([0, 0, 0, 0]),
+            1,
+        );
+
+        let extracted =
+            Pattern::from_flood_fill(&image, 2, 1, Connectivity::Eight, &COLOR_MATCHER).unwrap();
+
+        assert_eq!(extracted.get_coordinates(), crewmate.get_coordinates());
+        assert!(
+            Pattern::from_flood_fill(&image, 6, 0, Connectivity::Eight, &COLOR_MATCHER).is_none()
+        );
+    }
+
+    #[test]
+    fn test_to_image_round_trip() {
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 1, y: 0 },
+            Coordinate { x: 0, y: 1 },
+        ]);
+
+        let image = pattern.to_image(Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 255]));
+
+        assert_eq!(image.dimensions(), (2, 2));
+        assert_eq!(
+            Pattern::from_image(image, Rgba([0, 0, 0, 0]), 0).get_coordinates(),
+            pattern.get_coordinates()
+        );
+    }
+
+    #[test]
+    fn test_upscaled() {
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 1 },
+        ]);
+
+        let upscaled = pattern.upscaled(2);
+
+        assert_eq!(upscaled.get_coordinates().len(), 8);
+        assert_eq!(upscaled.get_window_size(), (4, 4));
+        assert!(upscaled.contains_coordinate(&Coordinate { x: 1, y: 1 }));
+        assert!(!upscaled.contains_coordinate(&Coordinate { x: 2, y: 1 }));
+        assert_eq!(upscaled.get_variant().scale, 2);
+        assert_eq!(upscaled.upscaled(3).get_variant().scale, 6);
+    }
+
+    #[test]
+    fn test_translated_and_normalized() {
+        let pattern = Pattern::new_masked(
+            vec![Coordinate { x: 3, y: 5 }, Coordinate { x: 4, y: 6 }],
+            vec![Coordinate { x: 2, y: 5 }],
+            vec![],
+        );
+
+        assert_eq!(pattern.get_window_size(), (3, 2));
+
+        let normalized = pattern.normalized();
+
+        assert_eq!(
+            normalized.get_coordinates(),
+            &vec![Coordinate { x: 1, y: 0 }, Coordinate { x: 2, y: 1 }]
+        );
+        assert_eq!(
+            normalized.get_must_not_coordinates(),
+            &vec![Coordinate { x: 0, y: 0 }]
+        );
+        assert_eq!(
+            normalized.translated(2, 5).get_coordinates(),
+            pattern.get_coordinates()
+        );
+        assert!(pattern.contains_coordinate(&Coordinate { x: 4, y: 6 }));
+        assert!(!normalized.contains_coordinate(&Coordinate { x: 4, y: 6 }));
+    }
+
+    #[test]
+    fn test_bounding_box_and_centroid() {
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: -1, y: 2 },
+            Coordinate { x: 3, y: 2 },
+            Coordinate { x: 1, y: 4 },
+        ]);
+
+        let bounding_box = pattern.bounding_box().unwrap();
+
+        assert_eq!((bounding_box.min_x, bounding_box.min_y), (-1, 2));
+        assert_eq!(pattern.get_window_size(), (5, 3));
+        assert_eq!(pattern.pixel_count(), 3);
+        assert_eq!(pattern.centroid(), Some((1.0, 8.0 / 3.0)));
+
+        let empty = Pattern::new_from_coordinates(vec![]);
+
+        assert!(empty.bounding_box().is_none());
+        assert!(empty.centroid().is_none());
+        assert_eq!(empty.get_window_size(), (0, 0));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let first = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 0 },
+        ]);
+        let second = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 1, y: 0 },
+            Coordinate { x: 0, y: 1 },
+        ]);
+
+        assert_eq!(
+            first.union(&second).get_coordinates(),
+            &vec![
+                Coordinate { x: 0, y: 0 },
+                Coordinate { x: 1, y: 0 },
+                Coordinate { x: 0, y: 1 },
+            ]
+        );
+        assert_eq!(
+            first.intersection(&second).get_coordinates(),
+            &vec![Coordinate { x: 1, y: 0 }]
+        );
+        assert_eq!(
+            first.difference(&second).get_coordinates(),
+            &vec![Coordinate { x: 0, y: 0 }]
+        );
+    }
 }
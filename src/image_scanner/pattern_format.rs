@@ -0,0 +1,430 @@
+use std::{collections::HashSet, error::Error, fmt, fs, path::Path};
+
+use image::Rgba;
+
+use super::{
+    color_utils::ColorUtils,
+    coordinate::Coordinate,
+    multi_color_pattern::{ColorRole, MultiColorPattern, RoleColor},
+    pattern::Pattern,
+};
+
+const PATTERN_SYMBOL: char = '#';
+const MUST_NOT_SYMBOL: char = '.';
+const DONT_CARE_SYMBOL: char = '?';
+/// Marks JSON grid cells that belong to no role, like don't-care cells in ASCII grids.
+const NO_ROLE_SYMBOL: char = '?';
+/// Symbols given to roles when a multi-color pattern is written as JSON.
+const ROLE_SYMBOLS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternFormatError {
+    InvalidSymbol {
+        line: usize,
+        column: usize,
+        symbol: char,
+    },
+    InvalidColor(String),
+    DuplicateSymbol(char),
+    UnknownRole(String),
+    TooManyRoles(usize),
+    Json(String),
+}
+
+impl fmt::Display for PatternFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternFormatError::InvalidSymbol {
+                line,
+                column,
+                symbol,
+            } => write!(
+                f,
+                "Invalid symbol '{}' at line {}, column {}",
+                symbol, line, column
+            ),
+            PatternFormatError::InvalidColor(color) => write!(f, "Invalid color: {}", color),
+            PatternFormatError::DuplicateSymbol(symbol) => {
+                write!(f, "Symbol '{}' is used by more than one role", symbol)
+            }
+            PatternFormatError::UnknownRole(name) => write!(f, "Unknown role: {}", name),
+            PatternFormatError::TooManyRoles(count) => write!(
+                f,
+                "{} roles can not be written, at most {} are supported",
+                count,
+                ROLE_SYMBOLS.len()
+            ),
+            PatternFormatError::Json(message) => write!(f, "Invalid pattern JSON: {}", message),
+        }
+    }
+}
+
+impl Error for PatternFormatError {}
+
+/// JSON form of a multi-color pattern, `grid` rows use the role symbols and `?` for
+/// cells that belong to no role.
+#[derive(Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+pub struct PatternDefinition {
+    pub roles: Vec<RoleDefinition>,
+    #[serde(default)]
+    pub distinct_roles: Vec<(String, String)>,
+    pub grid: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+pub struct RoleDefinition {
+    pub name: String,
+    pub symbol: char,
+    /// `#RRGGBB` or `#RRGGBBAA`, a missing color lets the role match any color.
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl Pattern {
+    /// Reads an ASCII grid, `#` cells must have the pattern color, `.` cells must not
+    /// have it and `?` cells are ignored.
+    pub fn from_ascii(text: &str) -> Result<Pattern, PatternFormatError> {
+        let mut coordinates = Vec::new();
+        let mut must_not_coordinates = Vec::new();
+        let mut dont_care_coordinates = Vec::new();
+
+        for (y, line) in text.lines().enumerate() {
+            for (x, symbol) in line.trim_end().chars().enumerate() {
+                let coordinate = Coordinate::new(x as i32, y as i32);
+
+                match symbol {
+                    PATTERN_SYMBOL => coordinates.push(coordinate),
+                    MUST_NOT_SYMBOL => must_not_coordinates.push(coordinate),
+                    DONT_CARE_SYMBOL => dont_care_coordinates.push(coordinate),
+                    _ => {
+                        return Err(PatternFormatError::InvalidSymbol {
+                            line: y + 1,
+                            column: x + 1,
+                            symbol,
+                        })
+                    }
+                }
+            }
+        }
+
+        Ok(Pattern::new_masked(
+            coordinates,
+            must_not_coordinates,
+            dont_care_coordinates,
+        ))
+    }
+
+    /// ASCII grid of the pattern window, cells without a role are written as don't-care
+    /// cells so reading the grid back adds no must-not cells.
+    pub fn to_ascii(&self) -> String {
+        let normalized = self.normalized();
+        let coordinates: HashSet<&Coordinate> = normalized.get_coordinates().iter().collect();
+        let must_not_coordinates: HashSet<&Coordinate> =
+            normalized.get_must_not_coordinates().iter().collect();
+        let (width, height) = normalized.get_window_size();
+
+        let mut text = String::new();
+
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                let coordinate = Coordinate::new(x, y);
+
+                text.push(if coordinates.contains(&coordinate) {
+                    PATTERN_SYMBOL
+                } else if must_not_coordinates.contains(&coordinate) {
+                    MUST_NOT_SYMBOL
+                } else {
+                    DONT_CARE_SYMBOL
+                });
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+impl MultiColorPattern {
+    pub fn from_definition(
+        definition: &PatternDefinition,
+    ) -> Result<MultiColorPattern, PatternFormatError> {
+        let mut role_coordinates: Vec<Vec<Coordinate>> = vec![Vec::new(); definition.roles.len()];
+        let mut symbols = HashSet::new();
+
+        for role in &definition.roles {
+            if role.symbol == NO_ROLE_SYMBOL || !symbols.insert(role.symbol) {
+                return Err(PatternFormatError::DuplicateSymbol(role.symbol));
+            }
+        }
+
+        for (y, line) in definition.grid.iter().enumerate() {
+            for (x, symbol) in line.trim_end().chars().enumerate() {
+                if symbol == NO_ROLE_SYMBOL {
+                    continue;
+                }
+
+                let role_index = definition
+                    .roles
+                    .iter()
+                    .position(|role| role.symbol == symbol)
+                    .ok_or(PatternFormatError::InvalidSymbol {
+                        line: y + 1,
+                        column: x + 1,
+                        symbol,
+                    })?;
+
+                role_coordinates[role_index].push(Coordinate::new(x as i32, y as i32));
+            }
+        }
+
+        let roles = definition
+            .roles
+            .iter()
+            .zip(role_coordinates)
+            .map(|(role, coordinates)| {
+                let color = match &role.color {
+                    Some(color) => RoleColor::Fixed(parse_color(color)?),
+                    None => RoleColor::Free,
+                };
+
+                Ok(ColorRole {
+                    name: role.name.clone(),
+                    color,
+                    pattern: Pattern::new_from_coordinates(coordinates),
+                })
+            })
+            .collect::<Result<Vec<ColorRole>, PatternFormatError>>()?;
+
+        let mut pattern = MultiColorPattern::new(roles);
+
+        for (first_role, second_role) in &definition.distinct_roles {
+            for name in [first_role, second_role] {
+                if pattern.role_index(name).is_none() {
+                    return Err(PatternFormatError::UnknownRole(name.clone()));
+                }
+            }
+
+            pattern.require_distinct(first_role, second_role);
+        }
+
+        Ok(pattern)
+    }
+
+    /// Roles get the symbols `A`, `B`, `C`, ... in their order.
+    pub fn to_definition(&self) -> Result<PatternDefinition, PatternFormatError> {
+        let normalized = self.normalized();
+        let roles = normalized.get_roles();
+
+        if roles.len() > ROLE_SYMBOLS.len() {
+            return Err(PatternFormatError::TooManyRoles(roles.len()));
+        }
+
+        let (width, height) = normalized.get_window_size();
+        let mut grid = vec![vec![NO_ROLE_SYMBOL; width as usize]; height as usize];
+        let mut role_definitions = Vec::new();
+
+        for (role, symbol) in roles.iter().zip(ROLE_SYMBOLS.chars()) {
+            for coordinate in role.pattern.get_coordinates() {
+                grid[coordinate.y as usize][coordinate.x as usize] = symbol;
+            }
+
+            role_definitions.push(RoleDefinition {
+                name: role.name.clone(),
+                symbol,
+                color: match role.color {
+                    RoleColor::Free => None,
+                    RoleColor::Fixed(color) => Some(ColorUtils::to_hex(color)),
+                },
+            });
+        }
+
+        Ok(PatternDefinition {
+            roles: role_definitions,
+            distinct_roles: self
+                .get_distinct_roles()
+                .iter()
+                .map(|(first, second)| (roles[*first].name.clone(), roles[*second].name.clone()))
+                .collect(),
+            grid: grid.into_iter().map(String::from_iter).collect(),
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<MultiColorPattern, PatternFormatError> {
+        let definition: PatternDefinition = serde_json::from_str(json)
+            .map_err(|error| PatternFormatError::Json(error.to_string()))?;
+
+        MultiColorPattern::from_definition(&definition)
+    }
+
+    pub fn to_json(&self) -> Result<String, PatternFormatError> {
+        serde_json::to_string_pretty(&self.to_definition()?)
+            .map_err(|error| PatternFormatError::Json(error.to_string()))
+    }
+}
+
+pub struct PatternIO {}
+
+impl PatternIO {
+    pub fn load_ascii(path: &str) -> Result<Pattern, Box<dyn Error>> {
+        Ok(Pattern::from_ascii(&fs::read_to_string(Path::new(path))?)?)
+    }
+
+    pub fn save_ascii(pattern: &Pattern, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(Path::new(path), pattern.to_ascii())?;
+        Ok(())
+    }
+
+    pub fn load_json(path: &str) -> Result<MultiColorPattern, Box<dyn Error>> {
+        Ok(MultiColorPattern::from_json(&fs::read_to_string(
+            Path::new(path),
+        )?)?)
+    }
+
+    pub fn save_json(pattern: &MultiColorPattern, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(Path::new(path), pattern.to_json()? + "\n")?;
+        Ok(())
+    }
+}
+
+fn parse_color(color: &str) -> Result<Rgba<u8>, PatternFormatError> {
+    let invalid_color = || PatternFormatError::InvalidColor(color.to_string());
+
+    let hex = color.strip_prefix('#').ok_or_else(invalid_color)?;
+
+    if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+        return Err(invalid_color());
+    }
+
+    let mut channels = [255u8; 4];
+
+    for (index, channel) in channels.iter_mut().take(hex.len() / 2).enumerate() {
+        *channel =
+            u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|_| invalid_color())?;
+    }
+
+    Ok(Rgba(channels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_color, PatternFormatError, PatternIO};
+    use crate::image_scanner::coordinate::Coordinate;
+    use crate::image_scanner::multi_color_pattern::{MultiColorPattern, RoleColor};
+    use crate::image_scanner::pattern::Pattern;
+    use crate::test_utils::load_image;
+    use image::Rgba;
+
+    const VISOR_JSON: &str = r##"{
+  "roles": [
+    { "name": "body", "symbol": "B" },
+    { "name": "visor", "symbol": "V", "color": "#51E9F4" }
+  ],
+  "distinct_roles": [["body", "visor"]],
+  "grid": [
+    "?BBB",
+    "BVVB",
+    "BBBB"
+  ]
+}"##;
+
+    #[test]
+    fn test_crewmate_ascii_matches_png() {
+        let crewmate_image = load_image("assets/images/crewmate.png");
+        let from_image = Pattern::from_image(crewmate_image, Rgba([0, 0, 0, 0]), 1);
+
+        let from_text = PatternIO::load_ascii("assets/patterns/crewmate.txt").unwrap();
+
+        assert_eq!(from_text.get_coordinates(), from_image.get_coordinates());
+        assert_eq!(from_text.get_must_not_coordinates().len(), 5);
+    }
+
+    #[test]
+    fn test_ascii_round_trip() {
+        let text = "#?.\n.##\n";
+
+        let pattern = Pattern::from_ascii(text).unwrap();
+
+        assert_eq!(
+            pattern.get_dont_care_coordinates(),
+            &vec![Coordinate { x: 1, y: 0 }]
+        );
+        assert_eq!(pattern.to_ascii(), text);
+    }
+
+    #[test]
+    fn test_ascii_of_unmasked_pattern_adds_no_must_not_cells() {
+        let pattern = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 1 },
+        ]);
+
+        let text = pattern.to_ascii();
+        let round_trip = Pattern::from_ascii(&text).unwrap();
+
+        assert_eq!(text, "#?\n?#\n");
+        assert_eq!(round_trip.get_coordinates(), pattern.get_coordinates());
+        assert!(round_trip.get_must_not_coordinates().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_ascii_symbol() {
+        assert_eq!(
+            Pattern::from_ascii("##\n#x").unwrap_err(),
+            PatternFormatError::InvalidSymbol {
+                line: 2,
+                column: 2,
+                symbol: 'x'
+            }
+        );
+    }
+
+    #[test]
+    fn test_json_roles() {
+        let pattern = MultiColorPattern::from_json(VISOR_JSON).unwrap();
+        let roles = pattern.get_roles();
+
+        assert_eq!(roles[0].name, "body");
+        assert_eq!(roles[0].color, RoleColor::Free);
+        assert_eq!(roles[0].pattern.get_coordinates().len(), 9);
+        assert_eq!(roles[1].color, RoleColor::Fixed(Rgba([81, 233, 244, 255])));
+        assert_eq!(pattern.get_distinct_roles(), &vec![(0, 1)]);
+
+        let round_trip = MultiColorPattern::from_json(&pattern.to_json().unwrap()).unwrap();
+
+        assert_eq!(
+            round_trip.to_definition().unwrap(),
+            pattern.to_definition().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_json_must_not_symbol_is_no_role_symbol() {
+        let json = VISOR_JSON.replace("?BBB", ".BBB");
+
+        assert_eq!(
+            MultiColorPattern::from_json(&json).unwrap_err(),
+            PatternFormatError::InvalidSymbol {
+                line: 1,
+                column: 1,
+                symbol: '.'
+            }
+        );
+    }
+
+    #[test]
+    fn test_json_unknown_role() {
+        let json = VISOR_JSON.replace("[\"body\", \"visor\"]", "[\"body\", \"legs\"]");
+
+        assert_eq!(
+            MultiColorPattern::from_json(&json).unwrap_err(),
+            PatternFormatError::UnknownRole(String::from("legs"))
+        );
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#FF000080"), Ok(Rgba([255, 0, 0, 128])));
+        assert!(parse_color("FF0000").is_err());
+        assert!(parse_color("#FF00").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use super::{coordinate::Coordinate, pattern::Pattern, ScanError};
+
+pub struct LibraryEntry {
+    pub name: String,
+    pub pattern: Pattern,
+    /// When matches of different patterns overlap, the one with higher priority is kept.
+    pub priority: i32,
+}
+
+/// Named patterns searched for together in a single pass over the image.
+#[derive(Default)]
+pub struct PatternLibrary {
+    entries: Vec<LibraryEntry>,
+}
+
+impl PatternLibrary {
+    pub fn new() -> Self {
+        PatternLibrary::default()
+    }
+
+    /// Fails for empty patterns, they could never be matched.
+    pub fn add_pattern(
+        &mut self,
+        name: &str,
+        pattern: Pattern,
+        priority: i32,
+    ) -> Result<(), ScanError> {
+        if pattern.get_coordinates().is_empty() {
+            return Err(ScanError::EmptyPattern);
+        }
+
+        self.entries.push(LibraryEntry {
+            name: name.to_string(),
+            pattern,
+            priority,
+        });
+
+        Ok(())
+    }
+
+    pub fn get_entries(&self) -> &Vec<LibraryEntry> {
+        &self.entries
+    }
+
+    /// Drops matches overlapping a match of a different pattern with higher priority
+    /// (ties are won by the pattern added first) and groups the rest by pattern name.
+    pub fn resolve_matches(
+        &self,
+        mut matches: Vec<(usize, Pattern)>,
+    ) -> HashMap<String, Vec<Pattern>> {
+        matches.sort_by_key(|(entry_index, found_pattern)| {
+            (
+                -self.entries[*entry_index].priority,
+                *entry_index,
+                found_pattern.get_coordinates().first().map(|c| (c.y, c.x)),
+            )
+        });
+
+        let mut claimed_pixels: HashMap<Coordinate, usize> = HashMap::new();
+        let mut grouped_matches: HashMap<String, Vec<Pattern>> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.name.clone(), Vec::new()))
+            .collect();
+
+        for (entry_index, found_pattern) in matches {
+            let overlaps_other_pattern = found_pattern.get_coordinates().iter().any(|coordinate| {
+                claimed_pixels
+                    .get(coordinate)
+                    .is_some_and(|claimed_by| *claimed_by != entry_index)
+            });
+
+            if overlaps_other_pattern {
+                continue;
+            }
+
+            for coordinate in found_pattern.get_coordinates() {
+                claimed_pixels.insert(*coordinate, entry_index);
+            }
+
+            grouped_matches
+                .get_mut(&self.entries[entry_index].name)
+                .expect("every entry has a group")
+                .push(found_pattern);
+        }
+
+        grouped_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PatternLibrary;
+    use crate::image_scanner::{coordinate::Coordinate, pattern::Pattern, ScanError};
+
+    fn line(length: i32, offset_x: i32) -> Pattern {
+        Pattern::new_from_coordinates(
+            (0..length)
+                .map(|x| Coordinate::new(x + offset_x, 0))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_add_pattern_rejects_empty_patterns() {
+        let mut library = PatternLibrary::new();
+        let must_not_only = Pattern::new_masked(vec![], vec![Coordinate::new(0, 0)], vec![]);
+
+        assert_eq!(
+            library.add_pattern("empty", line(0, 0), 0),
+            Err(ScanError::EmptyPattern)
+        );
+        assert_eq!(
+            library.add_pattern("must_not_only", must_not_only, 0),
+            Err(ScanError::EmptyPattern)
+        );
+        assert!(library.get_entries().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_matches_by_priority() {
+        let mut library = PatternLibrary::new();
+        library.add_pattern("short", line(2, 0), 0).unwrap();
+        library.add_pattern("long", line(3, 0), 1).unwrap();
+
+        let matches = vec![(0, line(2, 0)), (0, line(2, 10)), (1, line(3, 1))];
+
+        let resolved = library.resolve_matches(matches);
+
+        assert_eq!(resolved["long"].len(), 1);
+        assert_eq!(resolved["short"].len(), 1);
+        assert_eq!(
+            resolved["short"][0].get_coordinates()[0],
+            Coordinate::new(10, 0)
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use super::coordinate::Coordinate;
+
+/// Clockwise rotation of a pattern.
+#[derive(
+    Debug, PartialEq, Eq, Hash, Copy, Clone, serde_derive::Serialize, serde_derive::Deserialize,
+)]
+pub enum Rotation {
+    None,
+    Degrees90,
+    Degrees180,
+    Degrees270,
+}
+
+/// Orientation and size of a pattern relative to the image it was created from.
+#[derive(
+    Debug, PartialEq, Eq, Hash, Copy, Clone, serde_derive::Serialize, serde_derive::Deserialize,
+)]
+pub struct PatternVariant {
+    pub rotation: Rotation,
+    /// Mirrored horizontally before rotating.
+    pub mirrored: bool,
+    /// Integer upscaling factor, every pattern pixel covers `scale` x `scale` pixels.
+    pub scale: u32,
+}
+
+impl PatternVariant {
+    pub fn new(rotation: Rotation, mirrored: bool) -> Self {
+        PatternVariant {
+            rotation,
+            mirrored,
+            scale: 1,
+        }
+    }
+
+    pub fn identity() -> Self {
+        PatternVariant::new(Rotation::None, false)
+    }
+
+    pub fn all() -> Vec<PatternVariant> {
+        let mut variants = Vec::new();
+
+        for mirrored in [false, true] {
+            for rotation in [
+                Rotation::None,
+                Rotation::Degrees90,
+                Rotation::Degrees180,
+                Rotation::Degrees270,
+            ] {
+                variants.push(PatternVariant::new(rotation, mirrored));
+            }
+        }
+
+        variants
+    }
+
+    pub fn with_scale(self, scale: u32) -> Self {
+        PatternVariant { scale, ..self }
+    }
+
+    /// Transforms a coordinate around the origin, the result needs to be moved back to the origin.
+    pub fn apply(&self, coordinate: &Coordinate) -> Coordinate {
+        let x = if self.mirrored {
+            -coordinate.x
+        } else {
+            coordinate.x
+        };
+        let y = coordinate.y;
+
+        match self.rotation {
+            Rotation::None => Coordinate::new(x, y),
+            Rotation::Degrees90 => Coordinate::new(-y, x),
+            Rotation::Degrees180 => Coordinate::new(-x, -y),
+            Rotation::Degrees270 => Coordinate::new(y, -x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PatternVariant, Rotation};
+    use crate::image_scanner::coordinate::Coordinate;
+
+    #[test]
+    fn test_apply_rotation() {
+        let coordinate = Coordinate::new(2, 1);
+
+        assert_eq!(
+            PatternVariant::new(Rotation::Degrees90, false).apply(&coordinate),
+            Coordinate::new(-1, 2)
+        );
+        assert_eq!(
+            PatternVariant::new(Rotation::Degrees180, true).apply(&coordinate),
+            Coordinate::new(2, -1)
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use std::{error::Error, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanError {
+    ImageLoad {
+        path: String,
+        message: String,
+    },
+    PatternLargerThanImage {
+        pattern_size: (u32, u32),
+        image_size: (u32, u32),
+    },
+    /// Patterns without a pixel to match, like masks with only must-not cells, cannot
+    /// be searched.
+    EmptyPattern,
+    /// `Config::pattern_scales` is empty or contains 0.
+    InvalidPatternScales(Vec<u32>),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::ImageLoad { path, message } => {
+                write!(f, "Failed to load image {}: {}", path, message)
+            }
+            ScanError::PatternLargerThanImage {
+                pattern_size,
+                image_size,
+            } => write!(
+                f,
+                "Pattern of size {:?} does not fit into image of size {:?}",
+                pattern_size, image_size
+            ),
+            ScanError::EmptyPattern => write!(f, "Pattern has no pixels to match"),
+            ScanError::InvalidPatternScales(scales) => write!(
+                f,
+                "Pattern scales must be non-empty and above 0, got {:?}",
+                scales
+            ),
+        }
+    }
+}
+
+impl Error for ScanError {}
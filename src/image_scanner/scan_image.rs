@@ -1,136 +1,213 @@
-use std::sync::Arc;
+use std::borrow::Cow;
 
-use image::{DynamicImage, GenericImageView};
+use image::{DynamicImage, GenericImageView, RgbaImage};
 use rayon::prelude::*;
 
 use super::{
-    color_utils::ColorUtils, coordinate::Coordinate, image_utils::ImageUtils, pattern::Pattern,
+    color_utils::ColorMatcher, compiled_pattern::CompiledPattern, neighborhood::BorderRule,
+    pattern::Pattern, ScanError,
 };
 
+/// Also rejects empty patterns, they would match every window.
+pub fn check_pattern_fits(image: &DynamicImage, search_pattern: &Pattern) -> Result<(), ScanError> {
+    if search_pattern.get_coordinates().is_empty() {
+        return Err(ScanError::EmptyPattern);
+    }
+
+    let (img_width, img_height) = image.dimensions();
+    let (window_width, window_height) = search_pattern.get_window_size();
+
+    if window_width > img_width || window_height > img_height {
+        return Err(ScanError::PatternLargerThanImage {
+            pattern_size: (window_width, window_height),
+            image_size: (img_width, img_height),
+        });
+    }
+
+    Ok(())
+}
+
 pub fn scan_image(
     image: &DynamicImage,
     search_pattern: &Pattern,
-    pattern_search_tolerance: u8,
+    color_matcher: &ColorMatcher,
+    border_rule: &BorderRule,
+) -> Result<Vec<Pattern>, ScanError> {
+    check_pattern_fits(image, search_pattern)?;
+
+    Ok(scan_image_compiled(
+        image,
+        &CompiledPattern::new(search_pattern, border_rule),
+        color_matcher,
+    ))
+}
+
+/// Scans the raw RGBA pixels of the image, the pattern has to fit into the image.
+pub fn scan_image_compiled(
+    image: &DynamicImage,
+    compiled_pattern: &CompiledPattern,
+    color_matcher: &ColorMatcher,
 ) -> Vec<Pattern> {
-    let (img_width, img_height) = image.dimensions();
-    let (window_width, window_height) = search_pattern.get_window_size();
+    let pixels = rgba_pixels(image);
+    let image_size = pixels.dimensions();
+    let raw_pixels = pixels.as_raw().as_slice();
 
-    let adjacent_pixel_coordinates =
-        Arc::new(search_pattern.generate_coordinates_of_adjacent_pixels());
+    let (img_width, img_height) = image_size;
+    let (window_width, window_height) = compiled_pattern.get_window_size();
 
-    let found_patterns: Vec<Pattern> = (0..(img_height - window_height))
+    (0..=(img_height - window_height))
         .into_par_iter()
-        .flat_map(|offset_y| {
-            let adjacent_pixel_coordinates = Arc::clone(&adjacent_pixel_coordinates);
-
-            (0..(img_width - window_width))
-                .into_par_iter()
-                .filter_map(move |offset_x| {
-                    pattern_in_window(
-                        image,
-                        offset_x,
+        .flat_map_iter(|offset_y| {
+            (0..=(img_width - window_width))
+                .filter(move |offset_x| {
+                    compiled_pattern.matches_at(
+                        raw_pixels,
+                        image_size,
+                        *offset_x,
                         offset_y,
-                        search_pattern,
-                        pattern_search_tolerance,
-                        &adjacent_pixel_coordinates,
+                        color_matcher,
                     )
                 })
+                .map(move |offset_x| compiled_pattern.found_pattern_at(offset_x, offset_y))
         })
+        .collect()
+}
+
+/// Scans the image once and tests every search pattern at each window position.
+/// Found patterns are returned together with the index of the search pattern they matched,
+/// empty patterns and patterns larger than the image are skipped.
+pub fn scan_image_for_multiple_patterns(
+    image: &DynamicImage,
+    search_patterns: &[Pattern],
+    color_matcher: &ColorMatcher,
+    border_rule: &BorderRule,
+) -> Vec<(usize, Pattern)> {
+    let pixels = rgba_pixels(image);
+    let image_size = pixels.dimensions();
+    let raw_pixels = pixels.as_raw().as_slice();
+    let (img_width, img_height) = image_size;
+
+    let compiled_patterns: Vec<(usize, CompiledPattern)> = search_patterns
+        .iter()
+        .enumerate()
+        .filter(|(_, pattern)| check_pattern_fits(image, pattern).is_ok())
+        .map(|(pattern_index, pattern)| (pattern_index, CompiledPattern::new(pattern, border_rule)))
         .collect();
+    let compiled_patterns = &compiled_patterns;
 
-    found_patterns
+    (0..img_height)
+        .into_par_iter()
+        .flat_map_iter(|offset_y| {
+            (0..img_width).flat_map(move |offset_x| {
+                compiled_patterns
+                    .iter()
+                    .filter(move |(_, compiled_pattern)| {
+                        let (window_width, window_height) = compiled_pattern.get_window_size();
+
+                        offset_x + window_width <= img_width
+                            && offset_y + window_height <= img_height
+                            && compiled_pattern.matches_at(
+                                raw_pixels,
+                                image_size,
+                                offset_x,
+                                offset_y,
+                                color_matcher,
+                            )
+                    })
+                    .map(move |(pattern_index, compiled_pattern)| {
+                        (
+                            *pattern_index,
+                            compiled_pattern.found_pattern_at(offset_x, offset_y),
+                        )
+                    })
+            })
+        })
+        .collect()
 }
 
-fn pattern_in_window(
+/// Finds placements of the pattern that stick out of the image, at least `min_visible_ratio`
+/// of the pattern pixels has to be visible. Found patterns only contain the visible pixels.
+pub fn scan_image_edges(
     image: &DynamicImage,
-    offset_x: u32,
-    offset_y: u32,
     search_pattern: &Pattern,
-    pattern_search_tolerance: u8,
-    adjacent_pixel_coordinates: &Arc<Vec<Coordinate>>,
-) -> Option<Pattern> {
-    let (window_width, window_height) = search_pattern.get_window_size();
-    let window = image.view(offset_x, offset_y, window_width, window_height);
-    let pattern_coordinates = search_pattern.get_coordinates();
-
-    let first_pixel_color = window.get_pixel(
-        pattern_coordinates[0].x as u32,
-        pattern_coordinates[0].y as u32,
-    );
-
-    for coordinate in pattern_coordinates {
-        let pixel_color = window.get_pixel(coordinate.x as u32, coordinate.y as u32);
-
-        if !ColorUtils::equal_with_tolerance(
-            first_pixel_color,
-            pixel_color,
-            pattern_search_tolerance,
-        ) {
-            return None;
-        }
-    }
-
-    let window_with_border =
-        ImageUtils::create_view_with_border(image, offset_x, offset_y, window_width, window_height);
-    let (window_with_border_width, window_with_border_height) = {
-        let (width, height) = window_with_border.dimensions();
+    color_matcher: &ColorMatcher,
+    border_rule: &BorderRule,
+    min_visible_ratio: f32,
+) -> Vec<Pattern> {
+    let compiled_pattern = &CompiledPattern::new(search_pattern, border_rule);
+    let pixels = rgba_pixels(image);
+    let image_size = pixels.dimensions();
+    let raw_pixels = pixels.as_raw().as_slice();
+
+    let (img_width, img_height) = (image_size.0 as i32, image_size.1 as i32);
+    let (window_width, window_height) = {
+        let (width, height) = compiled_pattern.get_window_size();
         (width as i32, height as i32)
     };
 
-    let expanded_x_by = if offset_x == 0 { 0 } else { 1 };
-    let expanded_y_by = if offset_y == 0 { 0 } else { 1 };
-
-    for coordinate in adjacent_pixel_coordinates.iter() {
-        let adjusted_x = coordinate.x + expanded_x_by;
-        let adjusted_y = coordinate.y + expanded_y_by;
-
-        if adjusted_x < window_with_border_width
-            && adjusted_y < window_with_border_height
-            && adjusted_x >= 0
-            && adjusted_y >= 0
-        {
-            let adjacent_pixel_color =
-                window_with_border.get_pixel(adjusted_x as u32, adjusted_y as u32);
-
-            if ColorUtils::equal_with_tolerance(
-                first_pixel_color,
-                adjacent_pixel_color,
-                pattern_search_tolerance,
-            ) {
-                return None;
-            }
-        }
-    }
-
-    let coordinates_of_found_pattern = pattern_coordinates
-        .iter()
-        .map(|coord| Coordinate {
-            x: coord.x + offset_x as i32,
-            y: coord.y + offset_y as i32,
+    ((1 - window_height)..img_height)
+        .into_par_iter()
+        .flat_map_iter(|offset_y| {
+            ((1 - window_width)..img_width).filter_map(move |offset_x| {
+                let is_clipped = offset_x < 0
+                    || offset_y < 0
+                    || offset_x + window_width > img_width
+                    || offset_y + window_height > img_height;
+
+                if !is_clipped {
+                    return None;
+                }
+
+                compiled_pattern.clipped_match_at(
+                    raw_pixels,
+                    image_size,
+                    offset_x,
+                    offset_y,
+                    color_matcher,
+                    min_visible_ratio,
+                )
+            })
         })
-        .collect();
+        .collect()
+}
 
-    Some(Pattern::new_from_coordinates(coordinates_of_found_pattern))
+/// RGBA pixels of the image, only converted when the image is stored differently.
+fn rgba_pixels(image: &DynamicImage) -> Cow<'_, RgbaImage> {
+    match image.as_rgba8() {
+        Some(pixels) => Cow::Borrowed(pixels),
+        None => Cow::Owned(image.to_rgba8()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::image_scanner::neighborhood::{BorderRule, Connectivity};
     use crate::image_scanner::pattern::Pattern;
+    use crate::image_scanner::pattern_variant::{PatternVariant, Rotation};
+    use crate::test_utils::{load_image, BORDER_RULE, COLOR_MATCHER};
+
+    use super::{scan_image, scan_image_edges, scan_image_for_multiple_patterns};
+    use crate::image_scanner::color_utils::{AlphaHandling, ColorMatcher, ColorMetric};
+    use crate::image_scanner::coordinate::Coordinate;
+    use crate::image_scanner::ScanError;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    fn draw_pattern(pattern: &Pattern, width: u32, height: u32) -> DynamicImage {
+        let mut image = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+
+        for coordinate in pattern.get_coordinates() {
+            image.put_pixel(
+                coordinate.x as u32 + 1,
+                coordinate.y as u32 + 1,
+                Rgba([0, 0, 0, 255]),
+            );
+        }
 
-    use super::scan_image;
-    use image::io::Reader as ImageReader;
-    use image::Rgba;
-
-    fn load_image(image_path: &str) -> image::DynamicImage {
-        ImageReader::open(image_path)
-            .expect("Failed to open image")
-            .decode()
-            .expect("Failed to decode image")
+        DynamicImage::ImageRgba8(image)
     }
 
     const SEARCHED_COLOR: Rgba<u8> = Rgba([0, 0, 0, 0]);
     const PATTERN_EXTRACTING_TOLERANCE: u8 = 1;
-    const PATTERN_SEARCHING_TOLERANCE: u8 = 1;
 
     #[test]
     fn test_scan_image_simple() {
@@ -140,12 +217,40 @@ mod tests {
         let search_pattern =
             Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
 
-        let found_patterns =
-            scan_image(&scanned_image, &search_pattern, PATTERN_SEARCHING_TOLERANCE);
+        let found_patterns = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
 
         assert_eq!(found_patterns.len(), 1)
     }
 
+    #[test]
+    fn test_scan_image_translated_pattern() {
+        let pattern_image = load_image("assets/images/crewmate.png");
+        let scanned_image = load_image("assets/images/crewmate_with_borders.png");
+
+        let search_pattern =
+            Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
+
+        let found_patterns = scan_image(
+            &scanned_image,
+            &search_pattern.translated(-5, 7),
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
+
+        assert_eq!(found_patterns.len(), 1);
+        assert_eq!(
+            found_patterns[0].get_coordinates(),
+            search_pattern.translated(1, 1).get_coordinates()
+        );
+    }
+
     #[test]
     fn test_scan_image_different_colors() {
         let pattern_image = load_image("assets/images/crewmate.png");
@@ -154,8 +259,13 @@ mod tests {
         let search_pattern =
             Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
 
-        let found_patterns =
-            scan_image(&scanned_image, &search_pattern, PATTERN_SEARCHING_TOLERANCE);
+        let found_patterns = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
 
         assert_eq!(found_patterns.len(), 8)
     }
@@ -168,8 +278,13 @@ mod tests {
         let search_pattern =
             Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
 
-        let found_patterns =
-            scan_image(&scanned_image, &search_pattern, PATTERN_SEARCHING_TOLERANCE);
+        let found_patterns = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
 
         assert_eq!(found_patterns.len(), 4)
     }
@@ -182,9 +297,332 @@ mod tests {
         let search_pattern =
             Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
 
-        let found_patterns =
-            scan_image(&scanned_image, &search_pattern, PATTERN_SEARCHING_TOLERANCE);
+        let found_patterns = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
 
         assert_eq!(found_patterns.len(), 4)
     }
+
+    #[test]
+    fn test_scan_image_reports_variant() {
+        let pattern_image = load_image("assets/images/crewmate.png");
+        let crewmate =
+            Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
+        let mirrored_variant = PatternVariant::new(Rotation::None, true);
+        let search_pattern = crewmate.transformed(mirrored_variant);
+
+        let scanned_image = draw_pattern(&search_pattern, 6, 6);
+
+        let found_patterns = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
+
+        assert_eq!(found_patterns.len(), 1);
+        assert_eq!(found_patterns[0].get_variant(), mirrored_variant);
+    }
+
+    #[test]
+    fn test_scan_image_scaled() {
+        let pattern_image = load_image("assets/images/crewmate.png");
+        let crewmate =
+            Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
+        let scaled_crewmate = crewmate.upscaled(2);
+
+        let scanned_image = draw_pattern(&scaled_crewmate, 10, 10);
+
+        let found_scaled = scan_image(
+            &scanned_image,
+            &scaled_crewmate,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
+        let found_unscaled =
+            scan_image(&scanned_image, &crewmate, &COLOR_MATCHER, &BORDER_RULE).unwrap();
+
+        assert_eq!(found_scaled.len(), 1);
+        assert_eq!(found_scaled[0].get_variant().scale, 2);
+        assert!(found_unscaled.is_empty());
+    }
+
+    #[test]
+    fn test_scan_image_for_multiple_patterns() {
+        let crewmate = Pattern::from_image(
+            load_image("assets/images/crewmate.png"),
+            SEARCHED_COLOR,
+            PATTERN_EXTRACTING_TOLERANCE,
+        );
+        let mirrored_crewmate = crewmate.transformed(PatternVariant::new(Rotation::None, true));
+        let scanned_image = load_image("assets/images/8_crewmates.png");
+
+        let found_patterns = scan_image_for_multiple_patterns(
+            &scanned_image,
+            &[mirrored_crewmate, crewmate],
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        );
+
+        assert_eq!(found_patterns.len(), 8);
+        assert!(found_patterns
+            .iter()
+            .all(|(pattern_index, _)| *pattern_index == 1));
+    }
+
+    #[test]
+    fn test_scan_image_for_multiple_patterns_at_bottom_right_edge() {
+        let crewmate = Pattern::from_image(
+            load_image("assets/images/crewmate.png"),
+            SEARCHED_COLOR,
+            PATTERN_EXTRACTING_TOLERANCE,
+        );
+
+        let mut image = RgbaImage::from_pixel(6, 6, Rgba([255, 255, 255, 255]));
+        for coordinate in crewmate.get_coordinates() {
+            image.put_pixel(
+                coordinate.x as u32 + 2,
+                coordinate.y as u32 + 2,
+                Rgba([0, 0, 0, 255]),
+            );
+        }
+        let scanned_image = DynamicImage::ImageRgba8(image);
+
+        let found_patterns = scan_image_for_multiple_patterns(
+            &scanned_image,
+            &[crewmate],
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        );
+
+        assert_eq!(found_patterns.len(), 1);
+        assert!(found_patterns[0]
+            .1
+            .get_coordinates()
+            .contains(&Coordinate { x: 5, y: 5 }));
+    }
+
+    #[test]
+    fn test_scan_image_pattern_filling_image() {
+        let pattern_image = load_image("assets/images/crewmate.png");
+        let scanned_image = load_image("assets/images/crewmate.png");
+
+        let search_pattern =
+            Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
+
+        let found_patterns = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
+
+        assert_eq!(found_patterns.len(), 1)
+    }
+
+    #[test]
+    fn test_scan_image_pattern_larger_than_image() {
+        let pattern_image = load_image("assets/images/crewmate_with_borders.png");
+        let scanned_image = load_image("assets/images/crewmate.png");
+
+        let search_pattern = Pattern::from_image(
+            pattern_image,
+            Rgba([255, 255, 255, 255]),
+            PATTERN_EXTRACTING_TOLERANCE,
+        );
+
+        assert_eq!(
+            scan_image(
+                &scanned_image,
+                &search_pattern,
+                &COLOR_MATCHER,
+                &BORDER_RULE
+            )
+            .unwrap_err(),
+            ScanError::PatternLargerThanImage {
+                pattern_size: (6, 6),
+                image_size: (4, 4)
+            }
+        );
+    }
+
+    #[test]
+    fn test_empty_patterns_are_rejected() {
+        let scanned_image = load_image("assets/images/crewmate_with_borders.png");
+        let empty_pattern = Pattern::new_from_coordinates(vec![]);
+        let must_not_only = Pattern::new_masked(vec![], vec![Coordinate::new(0, 0)], vec![]);
+
+        for search_pattern in [&empty_pattern, &must_not_only] {
+            assert_eq!(
+                scan_image(&scanned_image, search_pattern, &COLOR_MATCHER, &BORDER_RULE)
+                    .unwrap_err(),
+                ScanError::EmptyPattern
+            );
+            assert!(scan_image_for_multiple_patterns(
+                &scanned_image,
+                std::slice::from_ref(search_pattern),
+                &COLOR_MATCHER,
+                &BORDER_RULE
+            )
+            .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_scan_image_edges() {
+        let pattern_image = load_image("assets/images/crewmate.png");
+        let search_pattern =
+            Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
+
+        let mut scanned_image = RgbaImage::from_pixel(6, 6, Rgba([255, 255, 255, 255]));
+        for coordinate in search_pattern.get_coordinates() {
+            if coordinate.x >= 1 {
+                scanned_image.put_pixel(
+                    coordinate.x as u32 - 1,
+                    coordinate.y as u32 + 1,
+                    Rgba([0, 0, 0, 255]),
+                );
+            }
+        }
+        let scanned_image = DynamicImage::ImageRgba8(scanned_image);
+
+        let found_patterns = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
+        let edge_patterns = scan_image_edges(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+            0.5,
+        );
+
+        assert!(found_patterns.is_empty());
+        assert_eq!(edge_patterns.len(), 1);
+        assert_eq!(edge_patterns[0].get_coordinates().len(), 9);
+    }
+
+    #[test]
+    fn test_scan_image_perceptual_metric() {
+        let pattern_image = load_image("assets/images/crewmate.png");
+        let search_pattern =
+            Pattern::from_image(pattern_image, SEARCHED_COLOR, PATTERN_EXTRACTING_TOLERANCE);
+
+        let mut scanned_image = draw_pattern(&search_pattern, 6, 6).into_rgba8();
+        // Compression noise on a single body pixel.
+        scanned_image.put_pixel(2, 1, Rgba([6, 4, 3, 255]));
+        let scanned_image = DynamicImage::ImageRgba8(scanned_image);
+
+        let delta_e_matcher =
+            ColorMatcher::new(ColorMetric::DeltaE2000, 5.0, AlphaHandling::Ignore);
+
+        let found_with_channel_delta = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
+        let found_with_delta_e = scan_image(
+            &scanned_image,
+            &search_pattern,
+            &delta_e_matcher,
+            &BORDER_RULE,
+        )
+        .unwrap();
+
+        assert!(found_with_channel_delta.is_empty());
+        assert_eq!(found_with_delta_e.len(), 1);
+    }
+
+    #[test]
+    fn test_scan_image_with_border_rules() {
+        let square = Pattern::new_from_coordinates(vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 0 },
+            Coordinate { x: 0, y: 1 },
+            Coordinate { x: 1, y: 1 },
+        ]);
+        let count_matches = |image: &DynamicImage, border_rule: BorderRule| {
+            scan_image(image, &square, &COLOR_MATCHER, &border_rule)
+                .unwrap()
+                .len()
+        };
+
+        let mut diagonal_neighbor = draw_pattern(&square, 8, 8).into_rgba8();
+        diagonal_neighbor.put_pixel(3, 3, Rgba([0, 0, 0, 255]));
+        let diagonal_neighbor = DynamicImage::ImageRgba8(diagonal_neighbor);
+
+        assert_eq!(count_matches(&diagonal_neighbor, BORDER_RULE), 0);
+        assert_eq!(
+            count_matches(&diagonal_neighbor, BorderRule::new(Connectivity::Four, 1)),
+            1
+        );
+        // Without a border any uniform area matches, the white background included.
+        assert!(scan_image(
+            &diagonal_neighbor,
+            &square,
+            &COLOR_MATCHER,
+            &BorderRule::None
+        )
+        .unwrap()
+        .iter()
+        .any(|found| found.get_coordinates()[0] == Coordinate { x: 1, y: 1 }));
+
+        let mut distant_neighbor = draw_pattern(&square, 8, 8).into_rgba8();
+        distant_neighbor.put_pixel(4, 1, Rgba([0, 0, 0, 255]));
+        let distant_neighbor = DynamicImage::ImageRgba8(distant_neighbor);
+
+        assert_eq!(count_matches(&distant_neighbor, BORDER_RULE), 1);
+        assert_eq!(
+            count_matches(&distant_neighbor, BorderRule::new(Connectivity::Four, 2)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_scan_image_with_masked_pattern() {
+        let square = vec![
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: 1, y: 0 },
+            Coordinate { x: 0, y: 1 },
+            Coordinate { x: 1, y: 1 },
+        ];
+        let masked_square = Pattern::new_masked(
+            square.clone(),
+            vec![Coordinate { x: 2, y: 1 }],
+            vec![Coordinate { x: 2, y: 0 }],
+        );
+        let square = Pattern::new_from_coordinates(square);
+
+        let mut with_accessory = draw_pattern(&square, 8, 8).into_rgba8();
+        with_accessory.put_pixel(3, 1, Rgba([0, 0, 0, 255]));
+        let with_accessory = DynamicImage::ImageRgba8(with_accessory);
+
+        let mut with_forbidden_pixel = draw_pattern(&square, 8, 8).into_rgba8();
+        with_forbidden_pixel.put_pixel(3, 2, Rgba([0, 0, 0, 255]));
+        let with_forbidden_pixel = DynamicImage::ImageRgba8(with_forbidden_pixel);
+
+        let scan = |image: &DynamicImage, pattern: &Pattern| {
+            scan_image(image, pattern, &COLOR_MATCHER, &BORDER_RULE)
+                .unwrap()
+                .len()
+        };
+
+        assert_eq!(scan(&with_accessory, &square), 0);
+        assert_eq!(scan(&with_accessory, &masked_square), 1);
+        assert_eq!(scan(&with_forbidden_pixel, &masked_square), 0);
+    }
 }
@@ -0,0 +1,157 @@
+use image::{DynamicImage, GenericImageView, Rgba};
+
+use super::{
+    bounding_box::BoundingBox, coordinate::Coordinate, pattern::Pattern,
+    pattern_variant::PatternVariant,
+};
+
+/// Pattern found in a scanned image. The fields starting with `image_` are in pixel
+/// coordinates of the scanned image, `canvas_origin` converts them to canvas coordinates.
+#[derive(Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+pub struct Match {
+    pub pattern_name: String,
+    pub variant: PatternVariant,
+    /// Canvas coordinate of the top-left pixel of the scanned image.
+    pub canvas_origin: Coordinate,
+    /// Bounding box of the matched pixels in image coordinates.
+    pub image_bounding_box: BoundingBox,
+    /// RGBA colors of the match, multi-color patterns have one color per role.
+    pub colors: Vec<[u8; 4]>,
+    /// Share of pattern and border pixels that are as expected, 1.0 for exact matches.
+    pub score: f32,
+    /// Every matched pixel in image coordinates.
+    pub image_coordinates: Vec<Coordinate>,
+    /// Pattern pixels of a different color and border pixels of the pattern color in
+    /// image coordinates, empty for exact matches.
+    pub image_deviating_coordinates: Vec<Coordinate>,
+}
+
+impl Match {
+    /// `canvas_origin` is the canvas coordinate of the top-left pixel of the scanned image.
+    pub fn new(
+        pattern_name: &str,
+        found_pattern: &Pattern,
+        colors: Vec<Rgba<u8>>,
+        score: f32,
+        canvas_origin: Coordinate,
+    ) -> Self {
+        let bounding_box = found_pattern
+            .bounding_box()
+            .expect("found patterns are never empty");
+
+        Match {
+            pattern_name: pattern_name.to_string(),
+            variant: found_pattern.get_variant(),
+            canvas_origin,
+            image_bounding_box: bounding_box,
+            colors: colors.iter().map(|color| color.0).collect(),
+            score,
+            image_coordinates: found_pattern.get_coordinates().clone(),
+            image_deviating_coordinates: Vec::new(),
+        }
+    }
+
+    /// Exact single-color match, its color is read from the first matched pixel.
+    pub fn from_found_pattern(
+        pattern_name: &str,
+        found_pattern: &Pattern,
+        image: &DynamicImage,
+        canvas_origin: Coordinate,
+    ) -> Self {
+        let first_coordinate = found_pattern.get_coordinates()[0];
+        let color = image.get_pixel(first_coordinate.x as u32, first_coordinate.y as u32);
+
+        Match::new(pattern_name, found_pattern, vec![color], 1.0, canvas_origin)
+    }
+
+    pub fn pixel_count(&self) -> usize {
+        self.image_coordinates.len()
+    }
+
+    /// Bounding box of the matched pixels in canvas coordinates.
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            self.image_bounding_box.min_x + self.canvas_origin.x,
+            self.image_bounding_box.min_y + self.canvas_origin.y,
+            self.image_bounding_box.max_x + self.canvas_origin.x,
+            self.image_bounding_box.max_y + self.canvas_origin.y,
+        )
+    }
+
+    /// Every matched pixel in canvas coordinates.
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        self.image_coordinates
+            .iter()
+            .map(|coordinate| {
+                Coordinate::new(
+                    coordinate.x + self.canvas_origin.x,
+                    coordinate.y + self.canvas_origin.y,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Match;
+    use crate::image_scanner::bounding_box::BoundingBox;
+    use crate::image_scanner::coordinate::Coordinate;
+    use crate::image_scanner::pattern::Pattern;
+    use crate::image_scanner::pattern_variant::{PatternVariant, Rotation};
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    #[test]
+    fn test_match_from_found_pattern() {
+        let mut image = RgbaImage::from_pixel(6, 6, Rgba([255, 255, 255, 255]));
+        image.put_pixel(3, 2, Rgba([255, 69, 0, 255]));
+        image.put_pixel(2, 3, Rgba([255, 69, 0, 255]));
+        let image = DynamicImage::ImageRgba8(image);
+
+        let found_pattern = Pattern::new_with_variant(
+            vec![Coordinate::new(3, 2), Coordinate::new(2, 3)],
+            PatternVariant::new(Rotation::Degrees90, false),
+        );
+
+        let found_match = Match::from_found_pattern(
+            "diagonal",
+            &found_pattern,
+            &image,
+            Coordinate::new(-1500, -1000),
+        );
+
+        assert_eq!(found_match.pattern_name, "diagonal");
+        assert_eq!(found_match.variant.rotation, Rotation::Degrees90);
+        assert_eq!(found_match.canvas_origin, Coordinate::new(-1500, -1000));
+        assert_eq!(found_match.image_bounding_box, BoundingBox::new(2, 2, 3, 3));
+        assert_eq!(
+            found_match.bounding_box(),
+            BoundingBox::new(-1498, -998, -1497, -997)
+        );
+        assert_eq!(
+            found_match.coordinates(),
+            vec![Coordinate::new(-1497, -998), Coordinate::new(-1498, -997)]
+        );
+        assert_eq!(found_match.colors, vec![[255, 69, 0, 255]]);
+        assert_eq!(found_match.score, 1.0);
+        assert_eq!(found_match.pixel_count(), 2);
+        assert!(found_match.image_deviating_coordinates.is_empty());
+    }
+
+    #[test]
+    fn test_match_serializes_to_json() {
+        let found_match = Match::new(
+            "dot",
+            &Pattern::new_from_coordinates(vec![Coordinate::new(4, 5)]),
+            vec![Rgba([0, 0, 0, 255])],
+            0.5,
+            Coordinate::new(10, 0),
+        );
+
+        let json = serde_json::to_string(&found_match).unwrap();
+
+        assert!(json.contains("\"pattern_name\":\"dot\""));
+        assert!(json.contains("\"canvas_origin\":{\"x\":10,\"y\":0}"));
+        assert_eq!(serde_json::from_str::<Match>(&json).unwrap(), found_match);
+    }
+}
@@ -0,0 +1,241 @@
+use image::{DynamicImage, GenericImageView, Rgba};
+use rayon::prelude::*;
+
+use super::{
+    color_utils::ColorMatcher,
+    coordinate::Coordinate,
+    multi_color_pattern::{ColorRole, MultiColorPattern, RoleColor},
+    neighborhood::BorderRule,
+    pattern::Pattern,
+    scan_image::check_pattern_fits,
+    ScanError,
+};
+
+/// Found patterns have every role fixed to the color it was matched with.
+pub fn scan_image_multi_color(
+    image: &DynamicImage,
+    search_pattern: &MultiColorPattern,
+    color_matcher: &ColorMatcher,
+    border_rule: &BorderRule,
+) -> Result<Vec<MultiColorPattern>, ScanError> {
+    let search_pattern = &search_pattern.normalized();
+    let shape = search_pattern.to_pattern();
+    check_pattern_fits(image, &shape)?;
+
+    let (img_width, img_height) = image.dimensions();
+    let (window_width, window_height) = shape.get_window_size();
+
+    let adjacent_pixel_coordinates = shape.generate_coordinates_of_adjacent_pixels(border_rule);
+    let adjacent_pixel_coordinates = &adjacent_pixel_coordinates;
+
+    let found_patterns = (0..=(img_height - window_height))
+        .into_par_iter()
+        .flat_map(|offset_y| {
+            (0..=(img_width - window_width))
+                .into_par_iter()
+                .filter_map(move |offset_x| {
+                    multi_color_pattern_in_window(
+                        image,
+                        offset_x as i32,
+                        offset_y as i32,
+                        search_pattern,
+                        color_matcher,
+                        adjacent_pixel_coordinates,
+                    )
+                })
+        })
+        .collect();
+
+    Ok(found_patterns)
+}
+
+fn multi_color_pattern_in_window(
+    image: &DynamicImage,
+    offset_x: i32,
+    offset_y: i32,
+    search_pattern: &MultiColorPattern,
+    color_matcher: &ColorMatcher,
+    adjacent_pixel_coordinates: &[Coordinate],
+) -> Option<MultiColorPattern> {
+    let get_pixel = |coordinate: &Coordinate| {
+        image.get_pixel(
+            (coordinate.x + offset_x) as u32,
+            (coordinate.y + offset_y) as u32,
+        )
+    };
+
+    let mut role_colors: Vec<Option<Rgba<u8>>> = Vec::new();
+
+    for role in search_pattern.get_roles() {
+        let coordinates = role.pattern.get_coordinates();
+
+        let role_color = match (role.color, coordinates.first()) {
+            (_, None) => None,
+            (RoleColor::Fixed(color), Some(_)) => Some(color),
+            (RoleColor::Free, Some(first_coordinate)) => Some(get_pixel(first_coordinate)),
+        };
+
+        if let Some(role_color) = role_color {
+            let is_uniform = coordinates
+                .iter()
+                .all(|coordinate| color_matcher.matches(role_color, get_pixel(coordinate)));
+
+            if !is_uniform {
+                return None;
+            }
+        }
+
+        role_colors.push(role_color);
+    }
+
+    for (first_index, second_index) in search_pattern.get_distinct_roles() {
+        if let (Some(first_color), Some(second_color)) =
+            (role_colors[*first_index], role_colors[*second_index])
+        {
+            if color_matcher.matches(first_color, second_color) {
+                return None;
+            }
+        }
+    }
+
+    let (img_width, img_height) = (image.width() as i32, image.height() as i32);
+
+    for coordinate in adjacent_pixel_coordinates {
+        let x = coordinate.x + offset_x;
+        let y = coordinate.y + offset_y;
+
+        if x < 0 || y < 0 || x >= img_width || y >= img_height {
+            continue;
+        }
+
+        let adjacent_pixel_color = image.get_pixel(x as u32, y as u32);
+
+        let touches_role_color = role_colors
+            .iter()
+            .flatten()
+            .any(|role_color| color_matcher.matches(*role_color, adjacent_pixel_color));
+
+        if touches_role_color {
+            return None;
+        }
+    }
+
+    let roles = search_pattern
+        .get_roles()
+        .iter()
+        .zip(role_colors)
+        .map(|(role, role_color)| ColorRole {
+            name: role.name.clone(),
+            color: role_color.map_or(RoleColor::Free, RoleColor::Fixed),
+            pattern: Pattern::new_with_variant(
+                role.pattern
+                    .get_coordinates()
+                    .iter()
+                    .map(|c| Coordinate::new(c.x + offset_x, c.y + offset_y))
+                    .collect(),
+                role.pattern.get_variant(),
+            ),
+        })
+        .collect();
+
+    Some(MultiColorPattern::new_with_distinct_roles(
+        roles,
+        search_pattern.get_distinct_roles().clone(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scan_image_multi_color;
+    use crate::image_scanner::multi_color_pattern::{MultiColorPattern, RoleColor, RoleMapping};
+    use crate::test_utils::{BORDER_RULE, COLOR_MATCHER};
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const BODY: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const VISOR: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    fn crewmate() -> MultiColorPattern {
+        let mut template = RgbaImage::from_pixel(2, 2, BODY);
+        template.put_pixel(1, 0, VISOR);
+
+        let mut pattern = MultiColorPattern::from_role_image(
+            DynamicImage::ImageRgba8(template),
+            &[
+                RoleMapping {
+                    template_color: BODY,
+                    name: "body".to_string(),
+                    color: RoleColor::Free,
+                },
+                RoleMapping {
+                    template_color: VISOR,
+                    name: "visor".to_string(),
+                    color: RoleColor::Free,
+                },
+            ],
+            0,
+        );
+        pattern.require_distinct("body", "visor");
+        pattern
+    }
+
+    fn draw(image: &mut RgbaImage, offset_x: u32, body: Rgba<u8>, visor: Rgba<u8>) {
+        image.put_pixel(offset_x, 1, body);
+        image.put_pixel(offset_x, 2, body);
+        image.put_pixel(offset_x + 1, 2, body);
+        image.put_pixel(offset_x + 1, 1, visor);
+    }
+
+    #[test]
+    fn test_scan_image_multi_color() {
+        let mut image = RgbaImage::from_pixel(12, 4, WHITE);
+        draw(&mut image, 1, Rgba([0, 0, 0, 255]), Rgba([0, 255, 0, 255]));
+        draw(&mut image, 5, Rgba([0, 0, 0, 255]), Rgba([0, 0, 0, 255]));
+        draw(
+            &mut image,
+            9,
+            Rgba([255, 0, 0, 255]),
+            Rgba([0, 0, 255, 255]),
+        );
+
+        let found_patterns = scan_image_multi_color(
+            &DynamicImage::ImageRgba8(image),
+            &crewmate(),
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
+
+        assert_eq!(found_patterns.len(), 2);
+        assert_eq!(
+            found_patterns[0].get_roles()[1].color,
+            RoleColor::Fixed(Rgba([0, 255, 0, 255]))
+        );
+    }
+
+    #[test]
+    fn test_scan_image_multi_color_fixed_role() {
+        let mut image = RgbaImage::from_pixel(8, 4, WHITE);
+        draw(&mut image, 1, Rgba([0, 0, 0, 255]), Rgba([0, 255, 0, 255]));
+        draw(&mut image, 5, Rgba([0, 0, 0, 255]), Rgba([0, 0, 255, 255]));
+
+        let mut pattern = crewmate();
+        let mut roles = pattern.get_roles().clone();
+        roles[1].color = RoleColor::Fixed(Rgba([0, 0, 255, 255]));
+        pattern = MultiColorPattern::new(roles);
+
+        let found_patterns = scan_image_multi_color(
+            &DynamicImage::ImageRgba8(image),
+            &pattern,
+            &COLOR_MATCHER,
+            &BORDER_RULE,
+        )
+        .unwrap();
+
+        assert_eq!(found_patterns.len(), 1);
+        assert_eq!(
+            found_patterns[0].get_roles()[0].pattern.get_coordinates()[0].x,
+            5
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::{collections::HashMap, ops::RangeInclusive};
+
+use image::Rgba;
+
+use super::{
+    bounding_box::BoundingBox, connected_components::ConnectedComponent, pattern::Pattern,
+};
+
+#[derive(Debug, Clone)]
+pub struct ShapeFrequency {
+    /// Canonical form of the shape, it can be searched for like any other pattern.
+    pub shape: Pattern,
+    pub shape_hash: u64,
+    pub occurrences: Vec<ShapeOccurrence>,
+}
+
+impl ShapeFrequency {
+    pub fn count(&self) -> usize {
+        self.occurrences.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeOccurrence {
+    pub color: Rgba<u8>,
+    pub bounding_box: BoundingBox,
+}
+
+/// Groups components with the same canonical shape, components with a pixel count outside
+/// of `pixel_counts` are skipped. Shapes are ordered from the most frequent one.
+pub fn count_shape_frequencies(
+    components: &[ConnectedComponent],
+    rotation_invariant: bool,
+    pixel_counts: RangeInclusive<u32>,
+) -> Vec<ShapeFrequency> {
+    let mut shapes: HashMap<Vec<_>, ShapeFrequency> = HashMap::new();
+
+    for component in components {
+        if !pixel_counts.contains(&component.pixel_count) {
+            continue;
+        }
+
+        let shape = component.shape.canonical(rotation_invariant);
+        let occurrence = ShapeOccurrence {
+            color: component.color,
+            bounding_box: component.bounding_box,
+        };
+
+        shapes
+            .entry(shape.get_coordinates().clone())
+            .or_insert_with(|| ShapeFrequency {
+                shape_hash: shape.shape_hash(),
+                shape,
+                occurrences: Vec::new(),
+            })
+            .occurrences
+            .push(occurrence);
+    }
+
+    let mut frequencies: Vec<ShapeFrequency> = shapes.into_values().collect();
+    frequencies.sort_by(|first, second| {
+        second
+            .count()
+            .cmp(&first.count())
+            .then_with(|| first.shape_hash.cmp(&second.shape_hash))
+    });
+
+    frequencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::count_shape_frequencies;
+    use crate::image_scanner::connected_components::ComponentLabeling;
+    use crate::image_scanner::neighborhood::Connectivity;
+    use crate::image_scanner::pattern::Pattern;
+    use crate::test_utils::{load_image, COLOR_MATCHER};
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    #[test]
+    fn test_most_frequent_shape_is_crewmate() {
+        let image = load_image("assets/images/8_crewmates.png");
+        let crewmate = load_image("assets/images/crewmate.png");
+        let crewmate = Pattern::from_image(crewmate, Rgba([0, 0, 0, 0]), 1);
+
+        let labeling = ComponentLabeling::from_image(&image, Connectivity::Eight, &COLOR_MATCHER);
+        let frequencies = count_shape_frequencies(labeling.get_components(), false, 2..=100);
+
+        assert_eq!(frequencies[0].count(), 8);
+        assert_eq!(
+            frequencies[0].shape.get_coordinates(),
+            crewmate.get_coordinates()
+        );
+    }
+
+    #[test]
+    fn test_rotation_invariant_frequencies() {
+        let black = Rgba([0, 0, 0, 255]);
+        let mut image = RgbaImage::from_pixel(8, 3, Rgba([255, 255, 255, 255]));
+        // An L shape and the same shape rotated by 180 degrees.
+        for (x, y) in [(0, 0), (0, 1), (1, 1), (4, 0), (5, 0), (5, 1)] {
+            image.put_pixel(x, y, black);
+        }
+        let image = DynamicImage::ImageRgba8(image);
+
+        let labeling = ComponentLabeling::from_image(&image, Connectivity::Four, &COLOR_MATCHER);
+
+        let frequencies = count_shape_frequencies(labeling.get_components(), false, 1..=10);
+        let invariant_frequencies =
+            count_shape_frequencies(labeling.get_components(), true, 1..=10);
+
+        assert_eq!(frequencies.len(), 2);
+        assert_eq!(invariant_frequencies.len(), 1);
+        assert_eq!(invariant_frequencies[0].count(), 2);
+        assert_eq!(invariant_frequencies[0].occurrences[0].color, black);
+    }
+}
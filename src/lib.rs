@@ -0,0 +1,11 @@
+#![allow(clippy::module_inception)]
+
+pub mod canvas_diff;
+pub mod canvas_replay;
+pub mod feed_parser;
+pub mod image_io;
+pub mod image_scanner;
+pub mod match_render;
+
+#[cfg(test)]
+mod test_utils;
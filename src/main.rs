@@ -1,27 +1,26 @@
-mod feed_parser;
-mod image_io;
-mod image_scanner;
-
 use std::time::Instant;
 
-use image_io::ImageIO;
-use image_scanner::ImageScanner;
-
-use crate::image_scanner::Config;
+use rusty_rplace_tools::feed_parser::CanvasRegion;
+use rusty_rplace_tools::image_io::ImageIO;
+use rusty_rplace_tools::image_scanner::{Config, Coordinate, ImageScanner};
 
 fn main() {
     let start_time = Instant::now();
-    let config = Config::new_default();
+    let canvas = CanvasRegion::new_2023_canvas();
+    let mut config = Config::new_default();
+    config.canvas_origin = Coordinate::new(canvas.x, canvas.y);
 
-    let scanner = ImageScanner::new(config);
+    let scanner = ImageScanner::new(config).unwrap();
 
     let target_image = ImageIO::load_image("assets/images/crewmate.png").unwrap();
     let source_image = ImageIO::load_image("assets/images/final_2023_place.png").unwrap();
 
     let search_pattern = scanner.create_pattern(target_image);
-    let found_patterns = scanner.scan_image_for_patterns(&search_pattern, &source_image);
+    let matches = scanner
+        .scan_image_for_patterns("crewmate", &search_pattern, &source_image)
+        .unwrap();
 
-    println!("{:?}", found_patterns.len());
+    println!("{:?}", matches.len());
 
     let end_time = Instant::now();
     let elapsed_time = end_time - start_time;
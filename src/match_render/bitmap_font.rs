@@ -0,0 +1,141 @@
+use image::{Rgba, RgbaImage};
+
+pub const GLYPH_WIDTH: u32 = 3;
+pub const GLYPH_HEIGHT: u32 = 5;
+const GLYPH_SPACING: u32 = 1;
+/// Space between the text of a label and the edge of its background.
+const LABEL_PADDING: u32 = 1;
+
+/// Rows of a glyph from top to bottom, the highest of the three bits is the left column.
+fn glyph(character: char) -> Option<[u8; 5]> {
+    let rows = match character {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        ' ' => [0b000; 5],
+        _ => return None,
+    };
+
+    Some(rows)
+}
+
+pub fn text_width(text: &str) -> u32 {
+    let length = text.chars().count() as u32;
+
+    (length * (GLYPH_WIDTH + GLYPH_SPACING)).saturating_sub(GLYPH_SPACING)
+}
+
+/// Draws `text` with its top-left corner at (`x`, `y`). Only digits, spaces and `-,.:#`
+/// are supported, other characters are left blank. Pixels outside the image are skipped.
+pub fn draw_text(image: &mut RgbaImage, text: &str, x: i32, y: i32, color: Rgba<u8>) {
+    for (index, character) in text.chars().enumerate() {
+        let glyph_x = x + (index as u32 * (GLYPH_WIDTH + GLYPH_SPACING)) as i32;
+
+        let rows = match glyph(character) {
+            Some(rows) => rows,
+            None => continue,
+        };
+
+        for (row_index, row) in rows.iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if row & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    put_pixel_clipped(image, glyph_x + column as i32, y + row_index as i32, color);
+                }
+            }
+        }
+    }
+}
+
+/// Size of a label of `text` including its background.
+pub fn label_size(text: &str) -> (u32, u32) {
+    (
+        text_width(text) + 2 * LABEL_PADDING,
+        GLYPH_HEIGHT + 2 * LABEL_PADDING,
+    )
+}
+
+/// Text on a filled background, the text is black or white depending on the background.
+pub fn draw_label(image: &mut RgbaImage, text: &str, x: i32, y: i32, background: Rgba<u8>) {
+    let (width, height) = label_size(text);
+
+    for label_y in 0..height as i32 {
+        for label_x in 0..width as i32 {
+            put_pixel_clipped(image, x + label_x, y + label_y, background);
+        }
+    }
+
+    let [r, g, b, _] = background.0;
+    let luma = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+    let text_color = if luma > 127 {
+        Rgba([0, 0, 0, 255])
+    } else {
+        Rgba([255, 255, 255, 255])
+    };
+
+    draw_text(
+        image,
+        text,
+        x + LABEL_PADDING as i32,
+        y + LABEL_PADDING as i32,
+        text_color,
+    );
+}
+
+pub fn put_pixel_clipped(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>) {
+    if x >= 0 && y >= 0 && (x as u32) < image.width() && (y as u32) < image.height() {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_label, draw_text, label_size, text_width};
+    use image::{Rgba, RgbaImage};
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(text_width(""), 0);
+        assert_eq!(text_width("7"), 3);
+        assert_eq!(text_width("-12"), 11);
+        assert_eq!(label_size("-12"), (13, 7));
+    }
+
+    #[test]
+    fn test_draw_text() {
+        let mut image = RgbaImage::from_pixel(8, 6, WHITE);
+
+        draw_text(&mut image, "1-", 0, 0, BLACK);
+
+        // Top of the one and the middle row of the dash.
+        assert_eq!(*image.get_pixel(1, 0), BLACK);
+        assert_eq!(*image.get_pixel(0, 0), WHITE);
+        assert_eq!(*image.get_pixel(4, 2), BLACK);
+        assert_eq!(*image.get_pixel(4, 1), WHITE);
+    }
+
+    #[test]
+    fn test_draw_label_clips_at_image_border() {
+        let mut image = RgbaImage::from_pixel(4, 4, BLACK);
+
+        draw_label(&mut image, "0", -1, -1, WHITE);
+
+        assert_eq!(*image.get_pixel(3, 3), WHITE);
+        // The left column of the zero is drawn on the first image column.
+        assert_eq!(*image.get_pixel(0, 0), BLACK);
+    }
+}
@@ -0,0 +1,280 @@
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+use crate::image_scanner::{ColorUtils, ImageUtils, Match};
+
+use super::bitmap_font::{draw_label, label_size};
+
+/// Space between thumbnails and around the edge of the sheet.
+const CELL_GAP: u32 = 2;
+/// Label background of matches without a color.
+const LABEL_COLOR: Rgba<u8> = Rgba([64, 64, 64, 255]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactSheetOrder {
+    /// Order in which the matches were passed in.
+    Found,
+    /// Grouped by the closest 2023 palette color of their first color.
+    Color,
+    /// Reading order of their canvas positions.
+    Position,
+    /// Best score first.
+    Score,
+}
+
+/// Grid of upscaled thumbnails of every match for quick review.
+pub struct ContactSheet {
+    /// Pixels of the scanned image shown around every match.
+    pub margin: u32,
+    /// Every image pixel becomes a `scale` x `scale` block.
+    pub scale: u32,
+    /// Thumbnails per row.
+    pub columns: u32,
+    pub order: ContactSheetOrder,
+    pub background: Rgba<u8>,
+}
+
+impl ContactSheet {
+    pub fn new(margin: u32, scale: u32, columns: u32) -> Self {
+        ContactSheet {
+            margin,
+            scale,
+            columns,
+            order: ContactSheetOrder::Found,
+            background: Rgba([255, 255, 255, 255]),
+        }
+    }
+
+    pub fn new_default() -> Self {
+        ContactSheet {
+            margin: 2,
+            scale: 4,
+            columns: 10,
+            order: ContactSheetOrder::Found,
+            background: Rgba([255, 255, 255, 255]),
+        }
+    }
+
+    /// Matches in the order of the sheet together with their index in `matches`.
+    pub fn sorted_matches<'a>(&self, matches: &'a [Match]) -> Vec<(usize, &'a Match)> {
+        let mut sorted: Vec<(usize, &Match)> = matches.iter().enumerate().collect();
+        let position = |found_match: &Match| {
+            let bounding_box = found_match.bounding_box();
+            (bounding_box.min_y, bounding_box.min_x)
+        };
+
+        match self.order {
+            ContactSheetOrder::Found => {}
+            ContactSheetOrder::Color => sorted.sort_by_key(|(_, found_match)| {
+                (
+                    found_match
+                        .colors
+                        .first()
+                        .map(|color| ColorUtils::palette_index(Rgba(*color))),
+                    found_match.colors.clone(),
+                    position(found_match),
+                )
+            }),
+            ContactSheetOrder::Position => {
+                sorted.sort_by_key(|(_, found_match)| position(found_match))
+            }
+            ContactSheetOrder::Score => sorted.sort_by(|(_, first), (_, second)| {
+                second
+                    .score
+                    .total_cmp(&first.score)
+                    .then_with(|| position(first).cmp(&position(second)))
+            }),
+        }
+
+        sorted
+    }
+
+    /// Thumbnails are cut out at the image coordinates of `matches`, so they have to be
+    /// found in `image`. Every thumbnail is labeled with the index of its match in `matches`
+    /// and the canvas position of the match.
+    pub fn render(&self, image: &DynamicImage, matches: &[Match]) -> DynamicImage {
+        let scale = self.scale.max(1);
+        let columns = self.columns.max(1);
+        let sorted = self.sorted_matches(matches);
+
+        let labels: Vec<String> = sorted
+            .iter()
+            .map(|(index, found_match)| {
+                let bounding_box = found_match.bounding_box();
+                format!("#{} {},{}", index, bounding_box.min_x, bounding_box.min_y)
+            })
+            .collect();
+        let (_, label_height) = label_size("");
+
+        let thumbnail_width = sorted
+            .iter()
+            .map(|(_, found_match)| {
+                (found_match.image_bounding_box.width() + 2 * self.margin) * scale
+            })
+            .max()
+            .unwrap_or(0);
+        let thumbnail_height = sorted
+            .iter()
+            .map(|(_, found_match)| {
+                (found_match.image_bounding_box.height() + 2 * self.margin) * scale
+            })
+            .max()
+            .unwrap_or(0);
+        let cell_width = labels
+            .iter()
+            .map(|label| label_size(label).0)
+            .max()
+            .unwrap_or(0)
+            .max(thumbnail_width);
+        let cell_height = thumbnail_height + label_height;
+
+        let rows = (sorted.len() as u32).div_ceil(columns);
+        let mut sheet = RgbaImage::from_pixel(
+            columns.min(sorted.len() as u32) * (cell_width + CELL_GAP) + CELL_GAP,
+            rows * (cell_height + CELL_GAP) + CELL_GAP,
+            self.background,
+        );
+
+        for (cell_index, ((_, found_match), label)) in sorted.iter().zip(&labels).enumerate() {
+            let cell_x = CELL_GAP + (cell_index as u32 % columns) * (cell_width + CELL_GAP);
+            let cell_y = CELL_GAP + (cell_index as u32 / columns) * (cell_height + CELL_GAP);
+
+            self.draw_thumbnail(&mut sheet, image, found_match, cell_x, cell_y);
+
+            let label_color = found_match
+                .colors
+                .first()
+                .map(|color| Rgba(*color))
+                .unwrap_or(LABEL_COLOR);
+
+            draw_label(
+                &mut sheet,
+                label,
+                cell_x as i32,
+                (cell_y + thumbnail_height) as i32,
+                label_color,
+            );
+        }
+
+        DynamicImage::ImageRgba8(sheet)
+    }
+
+    /// Upscaled view of the match and its margin. Views clipped by the image border keep
+    /// the match at the same place as unclipped ones.
+    fn draw_thumbnail(
+        &self,
+        sheet: &mut RgbaImage,
+        image: &DynamicImage,
+        found_match: &Match,
+        cell_x: u32,
+        cell_y: u32,
+    ) {
+        let scale = self.scale.max(1);
+        let bounding_box = found_match.image_bounding_box;
+        let offset_x = bounding_box.min_x as u32;
+        let offset_y = bounding_box.min_y as u32;
+
+        let view = ImageUtils::create_view_with_border(
+            image,
+            offset_x,
+            offset_y,
+            bounding_box.width(),
+            bounding_box.height(),
+            self.margin,
+        );
+        let clipped_x = self.margin - offset_x.min(self.margin);
+        let clipped_y = self.margin - offset_y.min(self.margin);
+
+        for (x, y, color) in view.pixels() {
+            for block_y in 0..scale {
+                for block_x in 0..scale {
+                    sheet.put_pixel(
+                        cell_x + (clipped_x + x) * scale + block_x,
+                        cell_y + (clipped_y + y) * scale + block_y,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{label_size, ContactSheet, ContactSheetOrder};
+    use crate::image_scanner::{Coordinate, Match, Pattern};
+    use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+    fn dot_match(x: i32, y: i32, color: Rgba<u8>, score: f32) -> Match {
+        Match::new(
+            "dot",
+            &Pattern::new_from_coordinates(vec![Coordinate::new(x, y)]),
+            vec![color],
+            score,
+            Coordinate::new(0, 0),
+        )
+    }
+
+    fn sorted_indices(sheet: &ContactSheet, matches: &[Match]) -> Vec<usize> {
+        sheet
+            .sorted_matches(matches)
+            .iter()
+            .map(|(index, _)| *index)
+            .collect()
+    }
+
+    #[test]
+    fn test_sorted_matches() {
+        let matches = vec![
+            dot_match(5, 5, WHITE, 0.5),
+            dot_match(1, 5, RED, 1.0),
+            dot_match(9, 0, BLACK, 0.8),
+        ];
+        let mut sheet = ContactSheet::new_default();
+
+        assert_eq!(sorted_indices(&sheet, &matches), vec![0, 1, 2]);
+
+        sheet.order = ContactSheetOrder::Position;
+        assert_eq!(sorted_indices(&sheet, &matches), vec![2, 1, 0]);
+
+        sheet.order = ContactSheetOrder::Score;
+        assert_eq!(sorted_indices(&sheet, &matches), vec![1, 2, 0]);
+
+        // Red comes before black and white in the 2023 palette.
+        sheet.order = ContactSheetOrder::Color;
+        assert_eq!(sorted_indices(&sheet, &matches), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_render_grid() {
+        let mut image = RgbaImage::from_pixel(10, 10, WHITE);
+        image.put_pixel(0, 0, RED);
+        image.put_pixel(5, 5, RED);
+        let image = DynamicImage::ImageRgba8(image);
+
+        let matches = vec![dot_match(0, 0, RED, 1.0), dot_match(5, 5, RED, 1.0)];
+        let mut sheet = ContactSheet::new(1, 2, 1);
+        sheet.background = BLACK;
+
+        let rendered = sheet.render(&image, &matches);
+        let (_, label_height) = label_size("");
+        let cell_height = 6 + label_height;
+
+        // Labels "#0 0,0" and "#1 5,5" are wider than the thumbnails.
+        assert_eq!(
+            rendered.dimensions(),
+            (2 + 25 + 2, 2 + 2 * (cell_height + 2))
+        );
+        // The first match is clipped by the image border, its margin stays empty.
+        assert_eq!(rendered.get_pixel(2, 2), BLACK);
+        assert_eq!(rendered.get_pixel(4, 4), RED);
+        assert_eq!(rendered.get_pixel(6, 6), WHITE);
+        // The second match is in the middle of its thumbnail.
+        let second_cell_y = 2 + cell_height + 2;
+        assert_eq!(rendered.get_pixel(2, second_cell_y), WHITE);
+        assert_eq!(rendered.get_pixel(4, second_cell_y + 2), RED);
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{HashMap, HashSet};
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+use crate::image_scanner::{BoundingBox, Coordinate, Match};
+
+use super::bitmap_font::{draw_label, label_size, put_pixel_clipped};
+
+/// Colors given to patterns without a color of their own, in order of their first match.
+const OVERLAY_COLORS: [Rgba<u8>; 6] = [
+    Rgba([255, 0, 255, 255]),
+    Rgba([0, 255, 0, 255]),
+    Rgba([0, 200, 255, 255]),
+    Rgba([255, 140, 0, 255]),
+    Rgba([255, 0, 0, 255]),
+    Rgba([255, 255, 0, 255]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayStyle {
+    /// Rectangle around the bounding box of every match.
+    BoundingBox,
+    /// Line around the matched pixels themselves.
+    Outline,
+}
+
+/// Draws scan results on top of the scanned image for visual review.
+pub struct MatchOverlay {
+    /// Every image pixel becomes a `scale` x `scale` block.
+    pub scale: u32,
+    pub style: OverlayStyle,
+    /// Draws the index of every match above its top-left corner.
+    pub show_labels: bool,
+    /// Fades pixels that are not part of any match.
+    pub dim_unmatched: bool,
+    /// Line colors by pattern name.
+    pub pattern_colors: HashMap<String, Rgba<u8>>,
+}
+
+impl MatchOverlay {
+    pub fn new(scale: u32, style: OverlayStyle) -> Self {
+        MatchOverlay {
+            scale,
+            style,
+            show_labels: false,
+            dim_unmatched: false,
+            pattern_colors: HashMap::new(),
+        }
+    }
+
+    pub fn new_default() -> Self {
+        MatchOverlay {
+            scale: 4,
+            style: OverlayStyle::Outline,
+            show_labels: true,
+            dim_unmatched: true,
+            pattern_colors: HashMap::new(),
+        }
+    }
+
+    pub fn set_pattern_color(&mut self, pattern_name: &str, color: Rgba<u8>) {
+        self.pattern_colors.insert(pattern_name.to_string(), color);
+    }
+
+    /// Upscaled image with every match marked, labels are the indices of the matches in `matches`.
+    /// Matches are drawn at their image coordinates, so they have to be found in `image`.
+    pub fn render(&self, image: &DynamicImage, matches: &[Match]) -> DynamicImage {
+        let scale = self.scale.max(1);
+        let (width, height) = image.dimensions();
+
+        let matched_pixels: HashSet<Coordinate> = matches
+            .iter()
+            .flat_map(|found_match| found_match.image_coordinates.iter().copied())
+            .collect();
+
+        let mut output = RgbaImage::new(width * scale, height * scale);
+
+        for (x, y, color) in image.pixels() {
+            let is_matched = matched_pixels.contains(&Coordinate::new(x as i32, y as i32));
+            let color = if self.dim_unmatched && !is_matched {
+                dim(color)
+            } else {
+                color
+            };
+
+            for block_y in 0..scale {
+                for block_x in 0..scale {
+                    output.put_pixel(x * scale + block_x, y * scale + block_y, color);
+                }
+            }
+        }
+
+        let colors = self.match_colors(matches);
+
+        for found_match in matches {
+            let color = colors[&found_match.pattern_name];
+            let bounding_box = found_match.image_bounding_box;
+
+            match self.style {
+                OverlayStyle::BoundingBox => {
+                    draw_outline(&mut output, scale, &bounding_box, color, |coordinate| {
+                        bounding_box.contains(coordinate)
+                    })
+                }
+                OverlayStyle::Outline => {
+                    let coordinates: HashSet<&Coordinate> =
+                        found_match.image_coordinates.iter().collect();
+
+                    draw_outline(&mut output, scale, &bounding_box, color, |coordinate| {
+                        coordinates.contains(coordinate)
+                    })
+                }
+            }
+        }
+
+        // Labels are drawn last so outlines of neighboring matches do not cover them.
+        if self.show_labels {
+            for (index, found_match) in matches.iter().enumerate() {
+                let label = index.to_string();
+                let (_, label_height) = label_size(&label);
+                let bounding_box = found_match.image_bounding_box;
+
+                // Above the outline, or below it when there is no room at the top of the image.
+                let mut label_y = bounding_box.min_y * scale as i32 - 1 - label_height as i32;
+                if label_y < 0 {
+                    label_y = (bounding_box.max_y + 1) * scale as i32 + 1;
+                }
+
+                draw_label(
+                    &mut output,
+                    &label,
+                    bounding_box.min_x * scale as i32 - 1,
+                    label_y,
+                    colors[&found_match.pattern_name],
+                );
+            }
+        }
+
+        DynamicImage::ImageRgba8(output)
+    }
+
+    /// Configured colors, patterns without one get the next unused color of `OVERLAY_COLORS`.
+    fn match_colors(&self, matches: &[Match]) -> HashMap<String, Rgba<u8>> {
+        let mut colors = self.pattern_colors.clone();
+        let mut next_color = 0;
+
+        for found_match in matches {
+            if !colors.contains_key(&found_match.pattern_name) {
+                colors.insert(
+                    found_match.pattern_name.clone(),
+                    OVERLAY_COLORS[next_color % OVERLAY_COLORS.len()],
+                );
+                next_color += 1;
+            }
+        }
+
+        colors
+    }
+}
+
+/// Colors the output pixels just outside the upscaled area of the image pixels accepted
+/// by `is_inside`, corners included.
+fn draw_outline<F>(
+    output: &mut RgbaImage,
+    scale: u32,
+    bounding_box: &BoundingBox,
+    color: Rgba<u8>,
+    is_inside: F,
+) where
+    F: Fn(&Coordinate) -> bool,
+{
+    let scale = scale as i32;
+    let output_is_inside =
+        |x: i32, y: i32| is_inside(&Coordinate::new(x.div_euclid(scale), y.div_euclid(scale)));
+
+    for y in (bounding_box.min_y * scale - 1)..=((bounding_box.max_y + 1) * scale) {
+        for x in (bounding_box.min_x * scale - 1)..=((bounding_box.max_x + 1) * scale) {
+            if output_is_inside(x, y) {
+                continue;
+            }
+
+            let touches_inside = (-1..=1).any(|offset_y| {
+                (-1..=1).any(|offset_x| output_is_inside(x + offset_x, y + offset_y))
+            });
+
+            if touches_inside {
+                put_pixel_clipped(output, x, y, color);
+            }
+        }
+    }
+}
+
+/// Keeps the hue of a color but moves it most of the way to white.
+fn dim(color: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = color.0;
+
+    Rgba([r / 4 + 191, g / 4 + 191, b / 4 + 191, 255])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatchOverlay, OverlayStyle};
+    use crate::image_scanner::{Coordinate, Match, Pattern};
+    use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+
+    fn image_with_match() -> (DynamicImage, Vec<Match>) {
+        let mut image = RgbaImage::from_pixel(8, 8, BLACK);
+        image.put_pixel(3, 3, RED);
+        image.put_pixel(4, 4, RED);
+
+        // Drawn at its image coordinates, the canvas position of the match is not used.
+        let found_pattern =
+            Pattern::new_from_coordinates(vec![Coordinate::new(3, 3), Coordinate::new(4, 4)]);
+        let found_match = Match::new(
+            "diagonal",
+            &found_pattern,
+            vec![RED],
+            1.0,
+            Coordinate::new(-1500, -1000),
+        );
+
+        (DynamicImage::ImageRgba8(image), vec![found_match])
+    }
+
+    #[test]
+    fn test_outline_overlay() {
+        let (image, matches) = image_with_match();
+        let mut overlay = MatchOverlay::new(2, OverlayStyle::Outline);
+        overlay.dim_unmatched = true;
+        overlay.set_pattern_color("diagonal", GREEN);
+
+        let rendered = overlay.render(&image, &matches);
+
+        assert_eq!(rendered.dimensions(), (16, 16));
+        assert_eq!(rendered.get_pixel(6, 6), RED);
+        // Pixels around the first matched block, the block of the second one is not outlined
+        // where it touches the first one diagonally.
+        assert_eq!(rendered.get_pixel(5, 5), GREEN);
+        assert_eq!(rendered.get_pixel(8, 6), GREEN);
+        assert_eq!(rendered.get_pixel(8, 8), RED);
+        assert_eq!(rendered.get_pixel(0, 0), Rgba([191, 191, 191, 255]));
+    }
+
+    #[test]
+    fn test_bounding_box_overlay_with_label() {
+        let (image, matches) = image_with_match();
+        let mut overlay = MatchOverlay::new(2, OverlayStyle::BoundingBox);
+        overlay.show_labels = true;
+
+        let rendered = overlay.render(&image, &matches);
+
+        // The box covers the gap between the diagonal pixels.
+        assert_eq!(rendered.get_pixel(10, 5), Rgba([255, 0, 255, 255]));
+        assert_eq!(rendered.get_pixel(8, 6), BLACK);
+        assert_eq!(rendered.get_pixel(0, 0), BLACK);
+        // There is no room above the box, so the label "0" is below it. Its background has
+        // the line color and the text is white on magenta.
+        assert_eq!(rendered.get_pixel(5, 11), Rgba([255, 0, 255, 255]));
+        assert_eq!(rendered.get_pixel(6, 12), Rgba([255, 255, 255, 255]));
+    }
+}
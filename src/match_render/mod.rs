@@ -0,0 +1,6 @@
+pub use contact_sheet::{ContactSheet, ContactSheetOrder};
+pub use match_overlay::{MatchOverlay, OverlayStyle};
+
+mod bitmap_font;
+mod contact_sheet;
+mod match_overlay;
@@ -0,0 +1,79 @@
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use csv::ReaderBuilder;
+use image::{io::Reader as ImageReader, DynamicImage};
+
+use crate::{
+    feed_parser::CSVRecord,
+    image_scanner::{AlphaHandling, BorderRule, ColorMatcher, ColorMetric, Connectivity},
+};
+
+/// Matcher the scanner tests search with, tolerating the off-by-one colors of the test images.
+pub const COLOR_MATCHER: ColorMatcher = ColorMatcher {
+    metric: ColorMetric::ChannelDelta,
+    tolerance: 1.0,
+    alpha_handling: AlphaHandling::Ignore,
+};
+/// Border of one pixel around a pattern, including its diagonal neighbours.
+pub const BORDER_RULE: BorderRule = BorderRule::Ring {
+    connectivity: Connectivity::Eight,
+    width: 1,
+};
+
+static NEXT_DIRECTORY_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Empty directory that is unique to one test and removed again when dropped.
+pub struct TempDirectory {
+    path: PathBuf,
+}
+
+impl TempDirectory {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "rplace_{}_{}_{}",
+            name,
+            std::process::id(),
+            NEXT_DIRECTORY_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+
+        TempDirectory { path }
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, file_name: &str) -> String {
+        self.path.join(file_name).to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for TempDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Records of a CSV string in the format of the r/place history files, header included.
+pub fn parse_records(csv_data: &str) -> Vec<CSVRecord> {
+    ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(Cursor::new(csv_data))
+        .deserialize()
+        .map(|record| record.unwrap())
+        .collect()
+}
+
+/// Image from the given path, panicking if it can't be read.
+pub fn load_image(image_path: &str) -> DynamicImage {
+    ImageReader::open(image_path)
+        .expect("Failed to open image")
+        .decode()
+        .expect("Failed to decode image")
+}